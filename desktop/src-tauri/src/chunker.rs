@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// 块大小下限：在此之前不检查切分点
+pub const MIN_CHUNK_SIZE: usize = 256 * 1024;
+/// 目标平均块大小
+pub const AVG_CHUNK_SIZE: usize = 1024 * 1024;
+/// 块大小上限
+pub const MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// 未到平均大小时使用更严格的掩码（22 位），超过后放宽（18 位），使块大小集中在平均值附近
+const MASK_STRICT: u64 = !0u64 << (64 - 22);
+const MASK_LOOSE: u64 = !0u64 << (64 - 18);
+
+/// 文件中的一个块
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    /// 块内容 SHA-256
+    pub hash: String,
+    pub size: u64,
+}
+
+/// 文件的块清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    pub size: u64,
+    /// 整个文件的 SHA-256
+    pub sha256: String,
+    pub chunks: Vec<ChunkRef>,
+}
+
+impl ChunkManifest {
+    /// 各块在文件中的偏移
+    pub fn offsets(&self) -> impl Iterator<Item = (u64, &ChunkRef)> {
+        self.chunks.iter().scan(0u64, |offset, chunk| {
+            let start = *offset;
+            *offset += chunk.size;
+            Some((start, chunk))
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Gear 滚动哈希的字节表，由固定种子生成，保证各端切分结果一致
+    static ref GEAR: [u64; 256] = {
+        let mut table = [0u64; 256];
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        for value in table.iter_mut() {
+            // splitmix64
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            *value = z ^ (z >> 31);
+        }
+        table
+    };
+}
+
+/// 在 data 中找到第一个切分点（FastCDC 风格的 Gear 滚动哈希），返回块长度
+///
+/// data 不足上限时视为文件末尾，剩余部分作为最后一块。
+pub fn cut_point(data: &[u8]) -> usize {
+    let len = data.len();
+    if len <= MIN_CHUNK_SIZE {
+        return len;
+    }
+    let normal = AVG_CHUNK_SIZE.min(len);
+    let max = MAX_CHUNK_SIZE.min(len);
+
+    let mut hash: u64 = 0;
+    let mut i = MIN_CHUNK_SIZE;
+    while i < normal {
+        hash = (hash << 1).wrapping_add(GEAR[data[i] as usize]);
+        if hash & MASK_STRICT == 0 {
+            return i + 1;
+        }
+        i += 1;
+    }
+    while i < max {
+        hash = (hash << 1).wrapping_add(GEAR[data[i] as usize]);
+        if hash & MASK_LOOSE == 0 {
+            return i + 1;
+        }
+        i += 1;
+    }
+    max
+}
+
+/// 读取到缓冲区满或文件结束
+fn fill(file: &mut File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let n = file.read(&mut buffer[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// 按内容切分文件，计算每块和整个文件的 SHA-256
+pub fn chunk_file(path: &Path) -> Result<ChunkManifest, String> {
+    let mut file = File::open(path).map_err(|e| format!("无法打开文件: {}", e))?;
+    let mut buffer = vec![0u8; MAX_CHUNK_SIZE];
+    let mut len = 0;
+    let mut eof = false;
+    let mut file_hasher = Sha256::new();
+    let mut chunks = Vec::new();
+    let mut size = 0u64;
+
+    loop {
+        if !eof && len < MAX_CHUNK_SIZE {
+            let n = fill(&mut file, &mut buffer[len..]).map_err(|e| format!("读取文件失败: {}", e))?;
+            eof = len + n < MAX_CHUNK_SIZE;
+            len += n;
+        }
+        if len == 0 {
+            break;
+        }
+
+        let cut = cut_point(&buffer[..len]);
+        let chunk = &buffer[..cut];
+        file_hasher.update(chunk);
+        chunks.push(ChunkRef {
+            hash: chunk_hash(chunk),
+            size: cut as u64,
+        });
+        size += cut as u64;
+
+        buffer.copy_within(cut..len, 0);
+        len -= cut;
+    }
+
+    Ok(ChunkManifest {
+        size,
+        sha256: hex::encode(file_hasher.finalize()),
+        chunks,
+    })
+}
+
+/// 读取文件中 offset 处的一块
+pub fn read_chunk(file: &mut File, offset: u64, size: u64) -> Result<Vec<u8>, String> {
+    file.seek(SeekFrom::Start(offset)).map_err(|e| format!("文件定位失败: {}", e))?;
+    let mut data = vec![0u8; size as usize];
+    file.read_exact(&mut data).map_err(|e| format!("读取文件失败: {}", e))?;
+    Ok(data)
+}
+
+/// 块内容的 SHA-256
+pub fn chunk_hash(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
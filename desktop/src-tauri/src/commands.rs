@@ -1,9 +1,18 @@
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use crate::scanner;
+use std::sync::{Arc, Mutex};
+use tauri::ipc::{InvokeBody, Request, Response};
+use crate::file_hash::{self, HashAlgorithm};
+use crate::folder_template;
+use crate::scanner::{self, EntryKind, Traversal, TraversalPolicy, Visit};
+use crate::selective_sync::SyncMode;
+use crate::sync_ignore::SyncIgnore;
+use crate::sync_roots;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupFolder {
     pub group_code: String,
     pub group_name: String,
@@ -11,15 +20,22 @@ pub struct GroupFolder {
     pub has_works: bool,
     pub has_models: bool,
     pub has_customer: bool,
+    /// 已存在的模板文件夹
+    pub folders: Vec<String>,
+    /// 选择性同步方式
+    pub sync_mode: SyncMode,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalFile {
     pub rel_path: String,
     pub filename: String,
     pub size: u64,
     pub modified_at: u64,
     pub is_dir: bool,
+    /// 云端占位文件对应的服务端存储键；rel_path 为真实文件路径
+    #[serde(default)]
+    pub placeholder: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,8 +56,17 @@ pub struct DirEntryInfo {
     pub is_dir: bool,
 }
 
+/// 扫描根目录；未指定时扫描全部根目录，重复的项目只保留优先级高的位置
 #[tauri::command]
-pub async fn scan_root_directory(root_path: String) -> Result<Vec<GroupFolder>, String> {
+pub async fn scan_root_directory(root_path: Option<String>) -> Result<Vec<GroupFolder>, String> {
+    let root_path = match root_path.filter(|p| !p.is_empty()) {
+        Some(p) => p,
+        None => {
+            return tokio::task::spawn_blocking(scan_all_roots)
+                .await
+                .map_err(|e| e.to_string());
+        }
+    };
     let path = Path::new(&root_path);
     
     if !path.exists() {
@@ -52,34 +77,23 @@ pub async fn scan_root_directory(root_path: String) -> Result<Vec<GroupFolder>,
         return Err("路径不是目录".to_string());
     }
 
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || scanner::scan_groups(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn scan_all_roots() -> Vec<GroupFolder> {
+    let mut seen = std::collections::HashSet::new();
     let mut groups = Vec::new();
-    
-    let entries = fs::read_dir(path).map_err(|e| format!("无法读取目录: {}", e))?;
-    
-    for entry in entries {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        
-        let entry_path = entry.path();
-        if !entry_path.is_dir() {
-            continue;
-        }
-        
-        let folder_name = match entry.file_name().to_str() {
-            Some(name) => name.to_string(),
-            None => continue,
-        };
-        
-        if let Some(group) = scanner::parse_group_folder(&folder_name, &entry_path) {
-            groups.push(group);
+    for root in sync_roots::all() {
+        match scanner::scan_groups(Path::new(&root.path)) {
+            Ok(found) => groups.extend(found.into_iter().filter(|g| seen.insert(g.group_code.clone()))),
+            Err(e) => log::warn!("[SyncRoots] 扫描 {} 失败: {}", root.path, e),
         }
     }
-    
     groups.sort_by(|a, b| a.group_code.cmp(&b.group_code));
-    
-    Ok(groups)
+    groups
 }
 
 #[tauri::command]
@@ -119,7 +133,7 @@ pub async fn list_dir_entries(dir_path: String) -> Result<Vec<DirEntryInfo>, Str
     Ok(items)
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FolderFileInfo {
     pub name: String,
     pub relative_path: String,
@@ -127,8 +141,12 @@ pub struct FolderFileInfo {
     pub size: u64,
 }
 
+/// 递归扫描文件夹中的文件；policy 未指定时不跟随链接，跳过的条目记录在日志中
 #[tauri::command]
-pub async fn scan_folder_recursive(folder_path: String) -> Result<Vec<FolderFileInfo>, String> {
+pub async fn scan_folder_recursive(
+    folder_path: String,
+    policy: Option<TraversalPolicy>,
+) -> Result<Vec<FolderFileInfo>, String> {
     let root = Path::new(&folder_path);
     
     if !root.exists() {
@@ -139,35 +157,65 @@ pub async fn scan_folder_recursive(folder_path: String) -> Result<Vec<FolderFile
         return Err("路径不是文件夹".to_string());
     }
     
-    let mut files = Vec::new();
-    scan_folder_inner(root, root, &mut files)?;
+    let started = std::time::Instant::now();
+    let root_dir = root.to_path_buf();
+    let (files, skipped, skipped_count) = tokio::task::spawn_blocking(move || {
+        let traversal = Traversal::new(policy.unwrap_or_default(), &root_dir);
+        let files = scan_folder_inner(&root_dir, &root_dir, &SyncIgnore::for_dir(&root_dir), &traversal, 1)?;
+        Ok::<_, String>((files, traversal.skipped(), traversal.skipped_count()))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    for entry in &skipped {
+        log::warn!("[Scanner] 跳过 {} ({:?})", entry.path, entry.reason);
+    }
+    log::info!(
+        "[Scanner] 扫描 {:?}：{} 个文件，跳过 {} 项，用时 {} ms",
+        root,
+        files.len(),
+        skipped_count,
+        started.elapsed().as_millis()
+    );
     
     Ok(files)
 }
 
-fn scan_folder_inner(root: &Path, current: &Path, files: &mut Vec<FolderFileInfo>) -> Result<(), String> {
-    let entries = fs::read_dir(current).map_err(|e| format!("无法读取目录: {}", e))?;
-    
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("无法读取目录项: {}", e))?;
-        let path = entry.path();
-        
-        if path.is_dir() {
-            scan_folder_inner(root, &path, files)?;
-        } else if path.is_file() {
-            let metadata = path.metadata().map_err(|e| format!("无法读取文件元数据: {}", e))?;
-            let relative = path.strip_prefix(root).map_err(|_| "无法计算相对路径")?;
+fn scan_folder_inner(
+    root: &Path,
+    current: &Path,
+    ignore: &SyncIgnore,
+    traversal: &Traversal,
+    depth: usize,
+) -> Result<Vec<FolderFileInfo>, String> {
+    let entries = scanner::list_dir(current)?;
+    
+    let groups: Vec<Vec<FolderFileInfo>> = entries
+        .par_iter()
+        .map(|(name, kind)| -> Result<Vec<FolderFileInfo>, String> {
+            let path = current.join(name);
+            if ignore.is_ignored(&path, *kind == EntryKind::Dir) {
+                return Ok(Vec::new());
+            }
             
-            files.push(FolderFileInfo {
-                name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
-                relative_path: relative.to_string_lossy().to_string().replace('\\', "/"),
-                absolute_path: path.to_string_lossy().to_string(),
-                size: metadata.len(),
-            });
-        }
-    }
+            match traversal.visit(&path, *kind, depth) {
+                Visit::Dir => scan_folder_inner(root, &path, &ignore.with_child(&path), traversal, depth + 1),
+                Visit::File => {
+                    let metadata = path.metadata().map_err(|e| format!("无法读取文件元数据: {}", e))?;
+                    let relative = path.strip_prefix(root).map_err(|_| "无法计算相对路径")?;
+                    
+                    Ok(vec![FolderFileInfo {
+                        name: name.clone(),
+                        relative_path: relative.to_string_lossy().to_string().replace('\\', "/"),
+                        absolute_path: path.to_string_lossy().to_string(),
+                        size: metadata.len(),
+                    }])
+                }
+                Visit::Skip => Ok(Vec::new()),
+            }
+        })
+        .collect::<Result<_, String>>()?;
     
-    Ok(())
+    Ok(groups.into_iter().flatten().collect())
 }
 
 #[tauri::command]
@@ -176,14 +224,13 @@ pub async fn get_local_files(
     group_code: String,
     asset_type: String,
 ) -> Result<Vec<LocalFile>, String> {
-    let asset_dir = match asset_type.as_str() {
-        "works" => "作品文件",
-        "models" => "模型文件",
-        "customer" => "客户文件",
-        _ => return Err("无效的资源类型".to_string()),
-    };
+    let asset_dir = folder_template::current()
+        .find(&asset_type)
+        .map(|f| f.name.clone())
+        .ok_or("无效的资源类型")?;
     
-    let entries = fs::read_dir(&root_path).map_err(|e| format!("无法读取目录: {}", e))?;
+    // 指定的根目录中没有该项目时到其他根目录查找
+    let entries = fs::read_dir(&root_path).into_iter().flatten();
     
     let mut target_path: Option<std::path::PathBuf> = None;
     
@@ -199,7 +246,7 @@ pub async fn get_local_files(
         };
         
         if folder_name.starts_with(&format!("{}_", group_code)) {
-            let asset_path = entry.path().join(asset_dir);
+            let asset_path = entry.path().join(&asset_dir);
             if asset_path.exists() {
                 target_path = Some(asset_path);
                 break;
@@ -207,42 +254,109 @@ pub async fn get_local_files(
         }
     }
     
-    let target_path = match target_path {
+    let target_path = match target_path.or_else(|| {
+        sync_roots::locate(&group_code)
+            .map(|l| Path::new(&l.group.path).join(&asset_dir))
+            .filter(|p| p.exists())
+    }) {
         Some(p) => p,
         None => return Ok(Vec::new()),
     };
     
-    let files = scanner::collect_files(&target_path, &target_path)?;
+    let ignore = SyncIgnore::for_dir(&target_path);
+    let files = scanner::collect_files(&target_path, &target_path, &ignore)?;
     
     Ok(files)
 }
 
-#[tauri::command]
-pub async fn calculate_file_hash(file_path: String) -> Result<String, String> {
-    use sha2::{Sha256, Digest};
-    use std::io::Read;
-    
-    let mut file = fs::File::open(&file_path)
-        .map_err(|e| format!("无法打开文件: {}", e))?;
-    
-    let mut hasher = Sha256::new();
-    let mut buffer = [0u8; 8192];
-    
-    loop {
-        let bytes_read = file.read(&mut buffer)
-            .map_err(|e| format!("读取文件失败: {}", e))?;
-        if bytes_read == 0 {
-            break;
+/// 计算文件 SHA-256，文件未变化时使用缓存
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    file_hash::hash_file(path, HashAlgorithm::Sha256)
+}
+
+/// 批量命令的并发上限
+const MAX_BATCH_CONCURRENCY: usize = 32;
+
+/// 批量命令中单个路径的结果，失败时 value 为空、error 为错误信息
+#[derive(Debug, Clone, Serialize)]
+pub struct PathResult<T> {
+    pub value: Option<T>,
+    pub error: Option<String>,
+}
+
+impl<T> From<Result<T, String>> for PathResult<T> {
+    fn from(result: Result<T, String>) -> Self {
+        match result {
+            Ok(value) => Self { value: Some(value), error: None },
+            Err(error) => Self { value: None, error: Some(error) },
         }
-        hasher.update(&buffer[..bytes_read]);
     }
-    
-    let result = hasher.finalize();
-    Ok(hex::encode(result))
+}
+
+lazy_static::lazy_static! {
+    /// 按并发数复用的线程池，避免每次批量调用都创建和销毁线程
+    static ref BATCH_POOLS: Mutex<HashMap<usize, Arc<rayon::ThreadPool>>> = Mutex::new(HashMap::new());
+}
+
+fn batch_pool(threads: usize) -> Result<Arc<rayon::ThreadPool>, String> {
+    let mut pools = BATCH_POOLS.lock().map_err(|e| e.to_string())?;
+    if let Some(pool) = pools.get(&threads) {
+        return Ok(pool.clone());
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(move |i| format!("batch-{}-{}", threads, i))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let pool = Arc::new(pool);
+    pools.insert(threads, pool.clone());
+    Ok(pool)
+}
+
+/// 在最多 concurrency 个线程上并行处理 paths，结果按路径返回；未指定并发数时使用 CPU 核数
+pub async fn run_batch<T, F>(
+    paths: Vec<String>,
+    concurrency: Option<usize>,
+    f: F,
+) -> Result<HashMap<String, PathResult<T>>, String>
+where
+    T: Send + 'static,
+    F: Fn(&str) -> Result<T, String> + Send + Sync + 'static,
+{
+    let threads = concurrency
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4))
+        .clamp(1, MAX_BATCH_CONCURRENCY);
+    tokio::task::spawn_blocking(move || {
+        let pool = batch_pool(threads)?;
+        Ok(pool.install(|| {
+            paths
+                .into_par_iter()
+                .map(|path| {
+                    let result = f(&path).into();
+                    (path, result)
+                })
+                .collect()
+        }))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
 pub async fn get_file_metadata(file_path: String) -> Result<FileMetadata, String> {
+    file_metadata(file_path)
+}
+
+/// 批量获取文件元数据，结果按路径返回，单个文件失败不影响其他文件
+#[tauri::command]
+pub async fn get_file_metadata_batch(
+    paths: Vec<String>,
+    concurrency: Option<usize>,
+) -> Result<HashMap<String, PathResult<FileMetadata>>, String> {
+    run_batch(paths, concurrency, |path| file_metadata(path.to_string())).await
+}
+
+fn file_metadata(file_path: String) -> Result<FileMetadata, String> {
     let path = Path::new(&file_path);
     
     if !path.exists() {
@@ -270,60 +384,107 @@ pub async fn get_file_metadata(file_path: String) -> Result<FileMetadata, String
     })
 }
 
+/// 读取文件分片，以二进制返回（前端收到 ArrayBuffer），读到文件末尾时返回的数据少于 length
 #[tauri::command]
 pub async fn read_file_chunk(
     file_path: String,
     offset: u64,
     length: u64,
-) -> Result<Vec<u8>, String> {
-    use std::io::{Read, Seek, SeekFrom};
-    
-    let mut file = fs::File::open(&file_path)
-        .map_err(|e| format!("无法打开文件: {}", e))?;
-    
-    file.seek(SeekFrom::Start(offset))
-        .map_err(|e| format!("文件定位失败: {}", e))?;
-    
-    let mut buffer = vec![0u8; length as usize];
-    let bytes_read = file.read(&mut buffer)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
-    
-    buffer.truncate(bytes_read);
-    Ok(buffer)
+) -> Result<Response, String> {
+    tokio::task::spawn_blocking(move || {
+        use std::io::{Read, Seek, SeekFrom};
+        
+        let mut file = fs::File::open(&file_path)
+            .map_err(|e| format!("无法打开文件: {}", e))?;
+        
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| format!("文件定位失败: {}", e))?;
+        
+        let mut buffer = Vec::new();
+        file.take(length)
+            .read_to_end(&mut buffer)
+            .map_err(|e| format!("读取文件失败: {}", e))?;
+        
+        Ok(Response::new(buffer))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+fn header<'a>(request: &'a Request<'_>, name: &str) -> Option<&'a str> {
+    request.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+/// 写入文件分片：请求体为二进制数据，参数通过请求头传递
+///
+/// - `x-file-path`：文件路径（URL 编码）
+/// - `x-append`：为 true 时追加到文件末尾
+/// - `x-offset`：写入位置，`x-append` 不为 true 时必填；在该位置覆盖写入，分片可乱序写入
+/// - `x-truncate`：为 true 时写入前清空文件（新建文件的第一个分片）
 #[tauri::command]
-pub async fn write_file_chunk(
-    file_path: String,
-    data: Vec<u8>,
-    append: bool,
-) -> Result<u64, String> {
-    use std::io::Write;
+pub async fn write_file_chunk(request: Request<'_>) -> Result<u64, String> {
+    use std::io::{Seek, SeekFrom, Write};
     
-    let path = Path::new(&file_path);
-    
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("创建目录失败: {}", e))?;
-        }
+    let data = match request.body() {
+        InvokeBody::Raw(data) => data.as_slice(),
+        InvokeBody::Json(_) => return Err("写入数据必须为二进制".to_string()),
+    };
+    let file_path = header(&request, "x-file-path").ok_or("缺少文件路径")?;
+    let file_path = percent_encoding::percent_decode_str(file_path)
+        .decode_utf8()
+        .map_err(|e| format!("文件路径无效: {}", e))?
+        .to_string();
+    let append = header(&request, "x-append") == Some("true");
+    let truncate = header(&request, "x-truncate") == Some("true");
+    let offset = match header(&request, "x-offset") {
+        Some(v) => Some(v.trim().parse::<u64>().map_err(|_| format!("写入位置无效: {}", v))?),
+        None => None,
+    };
+    if append && truncate {
+        return Err("追加写入不能清空文件".to_string());
     }
-    
-    let mut file = if append {
-        fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&file_path)
-            .map_err(|e| format!("打开文件失败: {}", e))?
-    } else {
-        fs::File::create(&file_path)
-            .map_err(|e| format!("创建文件失败: {}", e))?
+    let offset = match (append, offset) {
+        (true, None) => None,
+        (true, Some(_)) => return Err("追加写入不能指定写入位置".to_string()),
+        (false, Some(offset)) => Some(offset),
+        (false, None) => return Err("缺少写入位置".to_string()),
     };
     
-    let bytes_written = file.write(&data)
-        .map_err(|e| format!("写入文件失败: {}", e))?;
-    
-    Ok(bytes_written as u64)
+    // 直接写入借用的请求体，避免复制分片数据；Tauri 运行在多线程运行时上，可以使用 block_in_place
+    tokio::task::block_in_place(|| {
+        let path = Path::new(&file_path);
+        
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("创建目录失败: {}", e))?;
+            }
+        }
+        
+        let mut file = match offset {
+            Some(offset) => {
+                let mut file = fs::OpenOptions::new()
+                    .create(true)
+                    .truncate(truncate)
+                    .write(true)
+                    .open(path)
+                    .map_err(|e| format!("打开文件失败: {}", e))?;
+                file.seek(SeekFrom::Start(offset))
+                    .map_err(|e| format!("文件定位失败: {}", e))?;
+                file
+            }
+            None => fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("打开文件失败: {}", e))?,
+        };
+        
+        file.write_all(data)
+            .map_err(|e| format!("写入文件失败: {}", e))?;
+        
+        Ok(data.len() as u64)
+    })
 }
 
 #[tauri::command]
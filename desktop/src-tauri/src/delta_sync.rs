@@ -0,0 +1,437 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use crate::chunker::{self, ChunkManifest};
+use crate::download_cache;
+use crate::downloader;
+use crate::rate_limit::{self, RateLimiter};
+use crate::sync_client::{RemoteResource, SyncClient};
+use crate::sync_index::IndexEntry;
+
+/// 小于此大小的文件直接整体传输
+pub const DELTA_MIN_SIZE: u64 = 16 * 1024 * 1024;
+/// 每次询问服务端缺少哪些块的数量
+const QUERY_BATCH: usize = 500;
+
+/// 块上传的目标文件
+pub struct UploadTarget<'a> {
+    pub group_code: &'a str,
+    pub asset_type: &'a str,
+    pub rel_path: &'a str,
+    pub filename: &'a str,
+}
+
+/// 块同步协议，由 SyncClient 实现；返回 None 表示服务端不支持块同步，调用方改为整体传输
+pub trait ChunkTransport {
+    /// 服务端尚未保存的块
+    async fn missing_chunks(&self, hashes: &[String]) -> Result<Option<Vec<String>>, String>;
+    async fn put_chunk(&self, hash: &str, data: Vec<u8>) -> Result<(), String>;
+    /// 按块清单在服务端组装文件
+    async fn commit_chunks(&self, target: &UploadTarget<'_>, manifest: &ChunkManifest) -> Result<(), String>;
+    /// 服务端文件的块清单
+    async fn chunk_manifest(&self, storage_key: &str) -> Result<Option<ChunkManifest>, String>;
+    async fn get_chunk(&self, hash: &str) -> Result<Vec<u8>, String>;
+}
+
+async fn chunk_file(path: &Path) -> Result<ChunkManifest, String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || chunker::chunk_file(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 只上传服务端缺少的块，返回本地文件的块清单；服务端不支持时返回 None
+pub async fn upload_chunks<T: ChunkTransport>(
+    transport: &T,
+    target: &UploadTarget<'_>,
+    path: &Path,
+    limiter: &mut Option<RateLimiter>,
+) -> Result<Option<ChunkManifest>, String> {
+    let manifest = chunk_file(path).await?;
+
+    let mut unique: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for chunk in &manifest.chunks {
+        if seen.insert(chunk.hash.as_str()) {
+            unique.push(chunk.hash.clone());
+        }
+    }
+    let mut missing = HashSet::new();
+    for batch in unique.chunks(QUERY_BATCH) {
+        match transport.missing_chunks(batch).await? {
+            Some(m) => missing.extend(m),
+            None => return Ok(None),
+        }
+    }
+
+    let mut file = File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let mut sent_bytes = 0u64;
+    for (offset, chunk) in manifest.offsets() {
+        // 同一内容在文件中重复出现时只上传一次
+        if !missing.remove(&chunk.hash) {
+            continue;
+        }
+        let data = chunker::read_chunk(&mut file, offset, chunk.size)?;
+        if chunker::chunk_hash(&data) != chunk.hash {
+            return Err("上传过程中文件已变化".to_string());
+        }
+        transport.put_chunk(&chunk.hash, data).await?;
+        sent_bytes += chunk.size;
+        rate_limit::throttle(limiter, chunk.size).await;
+    }
+    transport.commit_chunks(target, &manifest).await?;
+
+    log::info!(
+        "[DeltaSync] 上传 {}：共 {} 块，发送 {} / {} 字节",
+        target.rel_path,
+        manifest.chunks.len(),
+        sent_bytes,
+        manifest.size
+    );
+    Ok(Some(manifest))
+}
+
+/// 下载服务端文件到临时文件，本地已有的块直接复用；服务端不支持时返回 None
+///
+/// local 为 save_path 处现有文件的块清单。组装后的内容与清单中的整体 SHA-256 不一致时返回错误。
+pub async fn download_chunks<T: ChunkTransport>(
+    transport: &T,
+    storage_key: &str,
+    save_path: &Path,
+    local: Option<&ChunkManifest>,
+    limiter: &mut Option<RateLimiter>,
+) -> Result<Option<(PathBuf, ChunkManifest)>, String> {
+    let remote = match transport.chunk_manifest(storage_key).await? {
+        Some(m) => m,
+        None => return Ok(None),
+    };
+
+    let reusable: HashMap<&str, (u64, u64)> = local
+        .map(|m| m.offsets().map(|(offset, c)| (c.hash.as_str(), (offset, c.size))).collect())
+        .unwrap_or_default();
+    let mut local_file = if reusable.is_empty() { None } else { File::open(save_path).ok() };
+
+    let mut part_name = save_path.as_os_str().to_os_string();
+    part_name.push(downloader::PARTIAL_SUFFIX);
+    let part_path = PathBuf::from(part_name);
+
+    let result = async {
+        if let Some(parent) = part_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let mut out = File::create(&part_path).map_err(|e| format!("创建文件失败: {}", e))?;
+        let mut hasher = Sha256::new();
+        let mut fetched_bytes = 0u64;
+        for chunk in &remote.chunks {
+            // 本地块在读取后校验，文件已被修改时改为下载
+            let local_data = match (reusable.get(chunk.hash.as_str()), local_file.as_mut()) {
+                (Some(&(offset, size)), Some(file)) => chunker::read_chunk(file, offset, size)
+                    .ok()
+                    .filter(|d| chunker::chunk_hash(d) == chunk.hash),
+                _ => None,
+            };
+            let data = match local_data {
+                Some(d) => d,
+                None => {
+                    let d = transport.get_chunk(&chunk.hash).await?;
+                    if chunker::chunk_hash(&d) != chunk.hash {
+                        return Err(format!("下载的块 {} 校验失败", chunk.hash));
+                    }
+                    fetched_bytes += d.len() as u64;
+                    rate_limit::throttle(limiter, d.len() as u64).await;
+                    d
+                }
+            };
+            hasher.update(&data);
+            out.write_all(&data).map_err(|e| format!("写入文件失败: {}", e))?;
+        }
+        out.flush().map_err(|e| format!("写入文件失败: {}", e))?;
+        // 块清单本身有误或块被错误复用时，不能替换本地文件
+        if hex::encode(hasher.finalize()) != remote.sha256 {
+            return Err("组装后的文件校验失败".to_string());
+        }
+        Ok(fetched_bytes)
+    }
+    .await;
+
+    match result {
+        Ok(fetched_bytes) => {
+            log::info!(
+                "[DeltaSync] 下载 {:?}：共 {} 块，接收 {} / {} 字节",
+                save_path,
+                remote.chunks.len(),
+                fetched_bytes,
+                remote.size
+            );
+            Ok(Some((part_path, remote)))
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&part_path);
+            Err(e)
+        }
+    }
+}
+
+/// 本地文件的块清单：与索引记录一致时直接使用，否则重新切分
+async fn local_manifest(path: &Path, base: Option<&IndexEntry>) -> Option<ChunkManifest> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified_at = metadata
+        .modified()
+        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+        .unwrap_or(0);
+
+    if let Some(entry) = base.filter(|e| e.size == metadata.len() && e.modified_at == modified_at) {
+        if let (Some(chunks), Some(hash)) = (&entry.chunks, &entry.hash) {
+            return Some(ChunkManifest { size: entry.size, sha256: hash.clone(), chunks: chunks.clone() });
+        }
+    }
+    chunk_file(path).await.ok()
+}
+
+/// 上传文件：大文件只上传变化的块，服务端不支持时整体上传
+///
+/// 按块上传时返回块清单，整体上传时返回分片大小，供调用方记入索引。
+pub async fn upload_file(
+    client: &SyncClient,
+    group_code: &str,
+    asset_type: &str,
+    rel_path: &str,
+    path: &Path,
+) -> Result<(Option<ChunkManifest>, Option<u64>), String> {
+    let size = fs::metadata(path).map_err(|e| format!("无法获取文件元数据: {}", e))?.len();
+    if size >= DELTA_MIN_SIZE && client.supports_delta().await {
+        let filename = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let target = UploadTarget { group_code, asset_type, rel_path, filename: &filename };
+        let mut limiter = RateLimiter::from_kbps(client.upload_limit_kbps());
+        if let Some(manifest) = upload_chunks(client, &target, path, &mut limiter).await? {
+            return Ok((Some(manifest), None));
+        }
+        log::info!("[DeltaSync] 服务端不支持块同步，整体上传 {}", rel_path);
+    }
+    let part_size = client.upload_file(group_code, asset_type, rel_path, path).await?;
+    Ok((None, Some(part_size)))
+}
+
+/// 下载服务端文件到临时文件，返回临时文件路径；本地已有旧版本的大文件只下载变化的块
+///
+/// 服务端提供内容哈希时先查下载缓存。
+/// base 为该文件的索引记录，用于取得本地文件的块清单。按块下载时同时返回块清单。
+pub async fn download_file(
+    client: &SyncClient,
+    remote: &RemoteResource,
+    path: &Path,
+    base: Option<&IndexEntry>,
+) -> Result<(PathBuf, Option<ChunkManifest>), String> {
+    if let Some(part_path) = download_cache::lookup(remote, path).await {
+        return Ok((part_path, None));
+    }
+    if remote.size >= DELTA_MIN_SIZE && client.supports_delta().await {
+        let local = local_manifest(path, base).await;
+        let mut limiter = RateLimiter::from_kbps(client.download_limit_kbps());
+        if let Some((part_path, manifest)) =
+            download_chunks(client, &remote.storage_key, path, local.as_ref(), &mut limiter).await?
+        {
+            return Ok((part_path, Some(manifest)));
+        }
+        log::info!("[DeltaSync] 服务端不支持块同步，整体下载 {}", remote.rel_path);
+    }
+    let url = client.download_url(&remote.storage_key).await?;
+    let (part_path, _) = downloader::download_to_partial(&url, path, client.download_limit_kbps()).await?;
+    Ok((part_path, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// 内存中的块存储，记录每次上传/下载的块
+    #[derive(Default)]
+    struct FakeTransport {
+        chunks: RefCell<HashMap<String, Vec<u8>>>,
+        manifests: RefCell<HashMap<String, ChunkManifest>>,
+        put: RefCell<Vec<String>>,
+        fetched: RefCell<Vec<String>>,
+        /// 询问缺少的块时改写该文件，模拟上传过程中文件被修改
+        modify_on_query: Option<PathBuf>,
+    }
+
+    impl ChunkTransport for FakeTransport {
+        async fn missing_chunks(&self, hashes: &[String]) -> Result<Option<Vec<String>>, String> {
+            if let Some(path) = &self.modify_on_query {
+                let mut data = fs::read(path).unwrap();
+                let last = data.len() - 1;
+                data[last] ^= 0xff;
+                fs::write(path, data).unwrap();
+            }
+            let chunks = self.chunks.borrow();
+            Ok(Some(hashes.iter().filter(|h| !chunks.contains_key(*h)).cloned().collect()))
+        }
+
+        async fn put_chunk(&self, hash: &str, data: Vec<u8>) -> Result<(), String> {
+            self.put.borrow_mut().push(hash.to_string());
+            self.chunks.borrow_mut().insert(hash.to_string(), data);
+            Ok(())
+        }
+
+        async fn commit_chunks(&self, target: &UploadTarget<'_>, manifest: &ChunkManifest) -> Result<(), String> {
+            let chunks = self.chunks.borrow();
+            if let Some(c) = manifest.chunks.iter().find(|c| !chunks.contains_key(&c.hash)) {
+                return Err(format!("缺少块 {}", c.hash));
+            }
+            self.manifests.borrow_mut().insert(target.rel_path.to_string(), manifest.clone());
+            Ok(())
+        }
+
+        async fn chunk_manifest(&self, storage_key: &str) -> Result<Option<ChunkManifest>, String> {
+            Ok(self.manifests.borrow().get(storage_key).cloned())
+        }
+
+        async fn get_chunk(&self, hash: &str) -> Result<Vec<u8>, String> {
+            self.fetched.borrow_mut().push(hash.to_string());
+            self.chunks.borrow().get(hash).cloned().ok_or_else(|| format!("块 {} 不存在", hash))
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("delta_sync_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 不可压缩的伪随机内容，足够切成多块
+    fn sample(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    fn target(rel_path: &str) -> UploadTarget<'_> {
+        UploadTarget { group_code: "Q0000000001", asset_type: "works", rel_path, filename: rel_path }
+    }
+
+    async fn upload(transport: &FakeTransport, rel_path: &str, path: &Path) -> Result<ChunkManifest, String> {
+        upload_chunks(transport, &target(rel_path), path, &mut None).await.map(|m| m.unwrap())
+    }
+
+    #[tokio::test]
+    async fn upload_sends_only_changed_chunks() {
+        let dir = temp_dir("upload_reuse");
+        let path = dir.join("a.bin");
+        let mut data = sample(6 * 1024 * 1024, 1);
+        fs::write(&path, &data).unwrap();
+
+        let transport = FakeTransport::default();
+        let first = upload(&transport, "a.bin", &path).await.unwrap();
+        assert!(first.chunks.len() > 2);
+        assert_eq!(transport.put.borrow().len(), first.chunks.len());
+
+        // 只改动文件末尾，前面的块应被复用
+        let len = data.len();
+        data[len - 10] ^= 0xff;
+        fs::write(&path, &data).unwrap();
+        transport.put.borrow_mut().clear();
+        let second = upload(&transport, "a.bin", &path).await.unwrap();
+        assert_eq!(second.chunks.len(), first.chunks.len());
+        assert_eq!(transport.put.borrow().as_slice(), &[second.chunks.last().unwrap().hash.clone()]);
+        assert_eq!(second.sha256, chunker::chunk_hash(&data));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn upload_fails_when_source_changes() {
+        let dir = temp_dir("upload_changed");
+        let path = dir.join("a.bin");
+        fs::write(&path, sample(3 * 1024 * 1024, 2)).unwrap();
+
+        let transport = FakeTransport { modify_on_query: Some(path.clone()), ..Default::default() };
+        let err = upload(&transport, "a.bin", &path).await.unwrap_err();
+        assert_eq!(err, "上传过程中文件已变化");
+        assert!(transport.manifests.borrow().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn download_reuses_local_chunks() {
+        let dir = temp_dir("download_reuse");
+        let remote_path = dir.join("remote.bin");
+        let local_path = dir.join("local.bin");
+        let mut data = sample(6 * 1024 * 1024, 3);
+        fs::write(&local_path, &data).unwrap();
+        let local = chunker::chunk_file(&local_path).unwrap();
+
+        data[10] ^= 0xff;
+        fs::write(&remote_path, &data).unwrap();
+        let transport = FakeTransport::default();
+        let remote = upload(&transport, "remote.bin", &remote_path).await.unwrap();
+
+        let (part_path, manifest) = download_chunks(&transport, "remote.bin", &local_path, Some(&local), &mut None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fs::read(&part_path).unwrap(), data);
+        assert_eq!(manifest.sha256, remote.sha256);
+        assert_eq!(transport.fetched.borrow().as_slice(), &[remote.chunks[0].hash.clone()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn download_creates_missing_parent_dir() {
+        let dir = temp_dir("download_new_dir");
+        let path = dir.join("a.bin");
+        let data = sample(3 * 1024 * 1024, 6);
+        fs::write(&path, &data).unwrap();
+        let transport = FakeTransport::default();
+        upload(&transport, "a.bin", &path).await.unwrap();
+
+        let save_path = dir.join("新建子目录").join("a.bin");
+        let (part_path, _) = download_chunks(&transport, "a.bin", &save_path, None, &mut None).await.unwrap().unwrap();
+        assert_eq!(fs::read(&part_path).unwrap(), data);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn download_fails_on_missing_chunk() {
+        let dir = temp_dir("download_missing");
+        let path = dir.join("a.bin");
+        fs::write(&path, sample(3 * 1024 * 1024, 4)).unwrap();
+        let transport = FakeTransport::default();
+        let remote = upload(&transport, "a.bin", &path).await.unwrap();
+        transport.chunks.borrow_mut().remove(&remote.chunks[1].hash);
+
+        let save_path = dir.join("b.bin");
+        let err = download_chunks(&transport, "a.bin", &save_path, None, &mut None).await.unwrap_err();
+        assert!(err.contains(&remote.chunks[1].hash));
+        assert!(!dir.join(format!("b.bin{}", downloader::PARTIAL_SUFFIX)).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn download_rejects_manifest_hash_mismatch() {
+        let dir = temp_dir("download_mismatch");
+        let path = dir.join("a.bin");
+        fs::write(&path, sample(3 * 1024 * 1024, 5)).unwrap();
+        let transport = FakeTransport::default();
+        upload(&transport, "a.bin", &path).await.unwrap();
+        transport.manifests.borrow_mut().get_mut("a.bin").unwrap().sha256 = "0".repeat(64);
+
+        let save_path = dir.join("b.bin");
+        let err = download_chunks(&transport, "a.bin", &save_path, None, &mut None).await.unwrap_err();
+        assert_eq!(err, "组装后的文件校验失败");
+        assert!(!dir.join(format!("b.bin{}", downloader::PARTIAL_SUFFIX)).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use crate::downloader;
+use crate::sync_client::RemoteResource;
+use crate::sync_engine;
+
+const OBJECTS_DIR_NAME: &str = "objects";
+const CATALOG_FILE_NAME: &str = "catalog.json";
+
+/// 下载缓存策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachePolicy {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 缓存总大小上限（MB），超出时清理最久未使用的文件，0 表示不限
+    #[serde(default = "default_max_size_mb")]
+    pub max_size_mb: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_size_mb() -> u64 {
+    10240
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            max_size_mb: default_max_size_mb(),
+        }
+    }
+}
+
+/// 缓存文件记录；大小或修改时间与记录不符时视为已被改动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    size: u64,
+    modified_at: u64,
+    last_used: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheCatalog {
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+/// 缓存统计
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub path: Option<String>,
+    pub entries: usize,
+    pub total_size: u64,
+    pub max_size_mb: u64,
+}
+
+/// 文件放入目标位置的方式
+#[derive(Debug, Clone, Copy)]
+enum LinkKind {
+    Reflink,
+    Copy,
+}
+
+lazy_static::lazy_static! {
+    static ref CACHE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+    static ref CATALOG_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 设置缓存目录（应用缓存目录下）
+pub fn init(cache_dir: PathBuf) {
+    if let Ok(mut dir) = CACHE_DIR.write() {
+        *dir = Some(cache_dir.join("downloads"));
+    }
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    CACHE_DIR.read().ok()?.clone()
+}
+
+fn policy() -> CachePolicy {
+    sync_engine::config().map(|c| c.cache).unwrap_or_default()
+}
+
+fn object_path(dir: &Path, hash: &str) -> PathBuf {
+    dir.join(OBJECTS_DIR_NAME).join(&hash[..2]).join(hash)
+}
+
+fn load_catalog(dir: &Path) -> CacheCatalog {
+    fs::read_to_string(dir.join(CATALOG_FILE_NAME))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_catalog(dir: &Path, catalog: &CacheCatalog) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let content = serde_json::to_string(catalog).map_err(|e| e.to_string())?;
+    let tmp = dir.join(format!("{}.tmp", CATALOG_FILE_NAME));
+    fs::write(&tmp, content).map_err(|e| format!("写入缓存目录失败: {}", e))?;
+    fs::rename(&tmp, dir.join(CATALOG_FILE_NAME)).map_err(|e| format!("保存缓存目录失败: {}", e))
+}
+
+fn file_state(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified_at = metadata
+        .modified()
+        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+        .unwrap_or(0);
+    Some((metadata.len(), modified_at))
+}
+
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// 服务端资源的缓存键：优先使用内容 SHA-256，否则由 ETag 和大小派生；两者都没有时不缓存
+fn key_of(remote: &RemoteResource) -> Option<String> {
+    if let Some(hash) = remote.hash.as_deref().filter(|h| is_valid_hash(h)) {
+        return Some(hash.to_ascii_lowercase());
+    }
+    let etag = remote.etag.as_deref().filter(|e| !e.is_empty())?;
+    Some(hex::encode(Sha256::digest(format!("etag:{}:{}", etag, remote.size))))
+}
+
+/// 优先写时复制，不支持时复制
+///
+/// 不使用硬链接：本地文件与缓存共用同一份内容时，在本地编辑会同时改动缓存和其他项目中的副本。
+fn materialize(from: &Path, to: &Path) -> Result<LinkKind, String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let _ = fs::remove_file(to);
+    if reflink_copy::reflink(from, to).is_ok() {
+        return Ok(LinkKind::Reflink);
+    }
+    fs::copy(from, to).map_err(|e| format!("复制文件失败: {}", e))?;
+    Ok(LinkKind::Copy)
+}
+
+/// 超出上限时按最久未使用清理
+fn evict(dir: &Path, catalog: &mut CacheCatalog, policy: &CachePolicy) -> u32 {
+    if policy.max_size_mb == 0 {
+        return 0;
+    }
+    let max_size = policy.max_size_mb * 1024 * 1024;
+    let mut total: u64 = catalog.entries.values().map(|e| e.size).sum();
+    if total <= max_size {
+        return 0;
+    }
+
+    let mut by_age: Vec<(i64, String)> = catalog
+        .entries
+        .iter()
+        .map(|(hash, e)| (e.last_used, hash.clone()))
+        .collect();
+    by_age.sort();
+
+    let mut removed = 0;
+    for (_, hash) in by_age {
+        if total <= max_size {
+            break;
+        }
+        if let Some(entry) = catalog.entries.remove(&hash) {
+            let _ = fs::remove_file(object_path(dir, &hash));
+            total = total.saturating_sub(entry.size);
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// 从缓存取出 remote 对应的文件，放到 `<target>.sync-part` 并返回该路径；未命中时返回 None
+pub async fn lookup(remote: &RemoteResource, target: &Path) -> Option<PathBuf> {
+    let key = key_of(remote)?;
+    let (size, target) = (remote.size, target.to_path_buf());
+    tokio::task::spawn_blocking(move || fetch(&key, size, &target)).await.ok().flatten()
+}
+
+/// 将刚下载到 path 的 remote 加入缓存
+pub async fn store(remote: &RemoteResource, path: &Path) {
+    let Some(key) = key_of(remote) else {
+        return;
+    };
+    let path = path.to_path_buf();
+    let _ = tokio::task::spawn_blocking(move || insert(&path, &key)).await;
+}
+
+fn fetch(hash: &str, size: u64, target: &Path) -> Option<PathBuf> {
+    if !policy().enabled || !is_valid_hash(hash) {
+        return None;
+    }
+    let dir = cache_dir()?;
+    let _lock = CATALOG_LOCK.lock().ok()?;
+    let mut catalog = load_catalog(&dir);
+    let entry = catalog.entries.get(hash)?.clone();
+    let object = object_path(&dir, hash);
+
+    if entry.size != size || file_state(&object) != Some((entry.size, entry.modified_at)) {
+        log::warn!("[DownloadCache] 缓存文件 {} 已被改动，移除", hash);
+        catalog.entries.remove(hash);
+        let _ = fs::remove_file(&object);
+        let _ = save_catalog(&dir, &catalog);
+        return None;
+    }
+
+    let mut part_name = target.as_os_str().to_os_string();
+    part_name.push(downloader::PARTIAL_SUFFIX);
+    let part_path = PathBuf::from(part_name);
+    let kind = match materialize(&object, &part_path) {
+        Ok(k) => k,
+        Err(e) => {
+            log::warn!("[DownloadCache] 取出 {} 失败: {}", hash, e);
+            return None;
+        }
+    };
+
+    if let Some(e) = catalog.entries.get_mut(hash) {
+        e.last_used = chrono::Utc::now().timestamp();
+    }
+    let _ = save_catalog(&dir, &catalog);
+    log::info!("[DownloadCache] 命中 {} ({:?}) -> {:?}", hash, kind, target);
+    Some(part_path)
+}
+
+/// 已存在时只更新使用时间
+fn insert(path: &Path, hash: &str) {
+    if !is_valid_hash(hash) {
+        return;
+    }
+    let policy = policy();
+    let dir = match cache_dir() {
+        Some(d) if policy.enabled => d,
+        _ => return,
+    };
+    let _lock = match CATALOG_LOCK.lock() {
+        Ok(l) => l,
+        Err(_) => return,
+    };
+    let mut catalog = load_catalog(&dir);
+    let object = object_path(&dir, hash);
+    let now = chrono::Utc::now().timestamp();
+
+    let cached = catalog
+        .entries
+        .get(hash)
+        .is_some_and(|e| file_state(&object) == Some((e.size, e.modified_at)));
+    if !cached {
+        let tmp = object.with_extension("tmp");
+        let _ = fs::remove_file(&object);
+        let stored = materialize(path, &tmp)
+            .and_then(|_| fs::rename(&tmp, &object).map_err(|e| e.to_string()));
+        if let Err(e) = stored {
+            let _ = fs::remove_file(&tmp);
+            log::warn!("[DownloadCache] 加入缓存失败 {:?}: {}", path, e);
+            return;
+        }
+        let (size, modified_at) = match file_state(&object) {
+            Some(s) => s,
+            None => return,
+        };
+        catalog.entries.insert(hash.to_string(), CacheEntry { size, modified_at, last_used: now });
+        let removed = evict(&dir, &mut catalog, &policy);
+        if removed > 0 {
+            log::info!("[DownloadCache] 清理最久未使用的 {} 个文件", removed);
+        }
+    } else if let Some(e) = catalog.entries.get_mut(hash) {
+        e.last_used = now;
+    }
+
+    if let Err(e) = save_catalog(&dir, &catalog) {
+        log::warn!("[DownloadCache] {}", e);
+    }
+}
+
+/// 获取下载缓存统计
+#[tauri::command]
+pub fn get_download_cache_stats() -> CacheStats {
+    let dir = cache_dir();
+    let catalog = dir.as_deref().map(load_catalog).unwrap_or_default();
+    CacheStats {
+        path: dir.map(|d| d.to_string_lossy().to_string()),
+        entries: catalog.entries.len(),
+        total_size: catalog.entries.values().map(|e| e.size).sum(),
+        max_size_mb: policy().max_size_mb,
+    }
+}
+
+/// 清空下载缓存，不影响项目中已下载的文件
+#[tauri::command]
+pub fn clear_download_cache() -> Result<(), String> {
+    let dir = cache_dir().ok_or("缓存目录未初始化")?;
+    let _lock = CATALOG_LOCK.lock().map_err(|e| e.to_string())?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| format!("清空缓存失败: {}", e))?;
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@ use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 use tauri::{AppHandle, Emitter};
+use crate::rate_limit::{self, RateLimiter};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadProgress {
@@ -29,6 +30,7 @@ pub async fn download_file(
     save_path: String,
 ) -> Result<DownloadResult, String> {
     let path = Path::new(&save_path);
+    crate::selective_sync::check_download_path(path)?;
     
     if let Some(parent) = path.parent() {
         if !parent.exists() {
@@ -128,6 +130,7 @@ pub async fn download_file_chunked(
     use futures_util::StreamExt;
     
     let path = Path::new(&save_path);
+    crate::selective_sync::check_download_path(path)?;
     
     if let Some(parent) = path.parent() {
         if !parent.exists() {
@@ -234,6 +237,67 @@ pub async fn download_file_chunked(
     })
 }
 
+/// 下载临时文件后缀，下载完成后再重命名为目标文件
+pub const PARTIAL_SUFFIX: &str = ".sync-part";
+
+/// 将 URL 内容流式下载到 `<save_path>.sync-part`（供后台同步使用），返回临时文件路径和写入字节数
+///
+/// 由调用方在处理完原文件后再替换目标文件。
+pub async fn download_to_partial(
+    url: &str,
+    save_path: &Path,
+    limit_kbps: Option<u64>,
+) -> Result<(std::path::PathBuf, u64), String> {
+    use futures_util::StreamExt;
+
+    if let Some(parent) = save_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+    }
+
+    let response = reqwest::Client::new()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP 错误: {}", response.status()));
+    }
+
+    let mut part_name = save_path.as_os_str().to_os_string();
+    part_name.push(PARTIAL_SUFFIX);
+    let part_path = std::path::PathBuf::from(part_name);
+
+    let mut file = File::create(&part_path)
+        .map_err(|e| format!("创建文件失败: {}", e))?;
+    let mut downloaded: u64 = 0;
+    let mut stream = response.bytes_stream();
+    let mut limiter = RateLimiter::from_kbps(limit_kbps);
+
+    while let Some(chunk_result) = stream.next().await {
+        let chunk = match chunk_result {
+            Ok(c) => c,
+            Err(e) => {
+                drop(file);
+                let _ = fs::remove_file(&part_path);
+                return Err(format!("下载块失败: {}", e));
+            }
+        };
+        file.write_all(&chunk)
+            .map_err(|e| format!("写入文件失败: {}", e))?;
+        downloaded += chunk.len() as u64;
+        rate_limit::throttle(&mut limiter, chunk.len() as u64).await;
+    }
+
+    file.flush().map_err(|e| format!("写入文件失败: {}", e))?;
+    drop(file);
+
+    Ok((part_path, downloaded))
+}
+
 #[tauri::command]
 pub async fn open_file_location(file_path: String) -> Result<(), String> {
     let path = Path::new(&file_path);
@@ -271,8 +335,16 @@ pub async fn open_file_location(file_path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 打开文件；云端占位文件先下载真实内容
 #[tauri::command]
 pub async fn open_file(file_path: String) -> Result<(), String> {
+    let file_path = match crate::placeholder::find(Path::new(&file_path)) {
+        Some(placeholder) => crate::placeholder::hydrate(&placeholder)
+            .await?
+            .to_string_lossy()
+            .to_string(),
+        None => file_path,
+    };
     let path = Path::new(&file_path);
     
     if !path.exists() {
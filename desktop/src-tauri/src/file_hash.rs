@@ -0,0 +1,210 @@
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter};
+use crate::commands::{self, PathResult};
+
+/// 读取缓冲区大小
+const BUFFER_SIZE: usize = 1024 * 1024;
+/// 超过此大小的文件发送进度事件
+const PROGRESS_MIN_SIZE: u64 = 256 * 1024 * 1024;
+/// 两次进度事件之间至少处理的字节数
+const PROGRESS_STEP: u64 = 64 * 1024 * 1024;
+/// 缓存条目上限，超出时清空重建
+const MAX_CACHED: usize = 20000;
+
+/// 哈希算法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    /// 同步索引和服务端校验使用
+    #[default]
+    Sha256,
+    /// 速度最快，适合本地比对
+    Blake3,
+    /// 与 S3 ETag（单段上传）一致
+    Md5,
+}
+
+/// 哈希计算进度事件
+#[derive(Debug, Clone, Serialize)]
+struct HashProgress {
+    file_path: String,
+    algorithm: HashAlgorithm,
+    processed: u64,
+    total: u64,
+}
+
+/// 缓存的哈希；文件大小或修改时间变化后失效
+struct CachedHash {
+    size: u64,
+    modified: SystemTime,
+    hash: String,
+}
+
+lazy_static::lazy_static! {
+    static ref HASH_CACHE: Mutex<HashMap<(PathBuf, String), CachedHash>> = Mutex::new(HashMap::new());
+}
+
+impl HashAlgorithm {
+    fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Md5 => "md5",
+        }
+    }
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+    Md5(Md5),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            HashAlgorithm::Md5 => Hasher::Md5(Md5::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Md5(h) => h.update(data),
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            Hasher::Sha256(h) => hex::encode(h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+            Hasher::Md5(h) => hex::encode(h.finalize()),
+        }
+    }
+}
+
+fn file_state(path: &Path) -> Result<(u64, SystemTime), String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("无法获取文件元数据: {}", e))?;
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    Ok((metadata.len(), modified))
+}
+
+fn cached(path: &Path, key: &str, size: u64, modified: SystemTime) -> Option<String> {
+    let cache = HASH_CACHE.lock().ok()?;
+    cache
+        .get(&(path.to_path_buf(), key.to_string()))
+        .filter(|c| c.size == size && c.modified == modified)
+        .map(|c| c.hash.clone())
+}
+
+fn store(path: &Path, key: &str, size: u64, modified: SystemTime, hash: &str) {
+    if let Ok(mut cache) = HASH_CACHE.lock() {
+        if cache.len() >= MAX_CACHED {
+            cache.clear();
+        }
+        cache.insert((path.to_path_buf(), key.to_string()), CachedHash { size, modified, hash: hash.to_string() });
+    }
+}
+
+/// 按 key 缓存基于文件内容计算的结果，文件大小和修改时间未变时直接返回
+pub fn cached_digest(
+    path: &Path,
+    key: &str,
+    compute: impl FnOnce() -> Result<String, String>,
+) -> Result<String, String> {
+    let (size, modified) = file_state(path)?;
+    if let Some(hash) = cached(path, key, size, modified) {
+        return Ok(hash);
+    }
+    let hash = compute()?;
+    // 计算期间文件被修改时不缓存
+    if file_state(path).ok() == Some((size, modified)) {
+        store(path, key, size, modified, &hash);
+    }
+    Ok(hash)
+}
+
+/// 计算文件哈希，文件大小和修改时间未变时使用缓存；progress 接收已处理字节数和文件大小
+pub fn hash_file_with_progress(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<String, String> {
+    cached_digest(path, algorithm.name(), || {
+        let mut file = File::open(path).map_err(|e| format!("无法打开文件: {}", e))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let mut hasher = Hasher::new(algorithm);
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut processed = 0u64;
+        let mut reported = 0u64;
+        loop {
+            let bytes_read = file.read(&mut buffer).map_err(|e| format!("读取文件失败: {}", e))?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+            processed += bytes_read as u64;
+            if processed - reported >= PROGRESS_STEP {
+                progress(processed, size);
+                reported = processed;
+            }
+        }
+        if reported > 0 && processed > reported {
+            progress(processed, size);
+        }
+        Ok(hasher.finalize())
+    })
+}
+
+/// 计算文件哈希，文件大小和修改时间未变时使用缓存
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> Result<String, String> {
+    hash_file_with_progress(path, algorithm, &mut |_, _| {})
+}
+
+/// 计算文件哈希（默认 SHA-256），大文件通过 hash-progress 事件报告进度
+#[tauri::command]
+pub async fn calculate_file_hash(
+    app: AppHandle,
+    file_path: String,
+    algorithm: Option<HashAlgorithm>,
+) -> Result<String, String> {
+    let algorithm = algorithm.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let path = PathBuf::from(&file_path);
+        let mut progress = |processed: u64, total: u64| {
+            if total >= PROGRESS_MIN_SIZE {
+                let _ = app.emit(
+                    "hash-progress",
+                    HashProgress { file_path: file_path.clone(), algorithm, processed, total },
+                );
+            }
+        };
+        hash_file_with_progress(&path, algorithm, &mut progress)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 批量计算文件哈希（默认 SHA-256），结果按路径返回，单个文件失败不影响其他文件
+#[tauri::command]
+pub async fn calculate_file_hash_batch(
+    paths: Vec<String>,
+    algorithm: Option<HashAlgorithm>,
+    concurrency: Option<usize>,
+) -> Result<HashMap<String, PathResult<String>>, String> {
+    let algorithm = algorithm.unwrap_or_default();
+    commands::run_batch(paths, concurrency, move |path| hash_file(Path::new(path), algorithm)).await
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 默认静默窗口：大小和修改时间在此期间不变才视为写入完成
+pub const DEFAULT_QUIET_WINDOW_SECS: u64 = 10;
+
+/// 文件写入状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stability {
+    /// 已稳定，可以上传
+    Stable,
+    /// 大小或修改时间仍在变化
+    Changing,
+    /// 被其他程序独占打开
+    Locked,
+    /// 存在编辑器锁文件/临时文件
+    EditorBusy,
+}
+
+impl Stability {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Stability::Stable => "已就绪",
+            Stability::Changing => "等待写入完成",
+            Stability::Locked => "文件被占用，等待写入完成",
+            Stability::EditorBusy => "编辑器正在保存，等待写入完成",
+        }
+    }
+}
+
+struct Observation {
+    size: u64,
+    modified: Option<SystemTime>,
+    since: Instant,
+}
+
+lazy_static::lazy_static! {
+    static ref OBSERVED: Mutex<HashMap<PathBuf, Observation>> = Mutex::new(HashMap::new());
+}
+
+/// 检查文件是否已写入完成
+///
+/// 需同时满足：未被独占打开、没有编辑器锁/临时文件、
+/// 大小与修改时间在静默窗口内保持不变（首次观察时按修改时间距今判断）。
+pub fn check(path: &Path, quiet_window: Duration) -> Stability {
+    let metadata = match std::fs::metadata(path) {
+        Ok(m) => m,
+        Err(_) => return Stability::Changing,
+    };
+
+    if has_editor_sibling(path) {
+        return Stability::EditorBusy;
+    }
+    if is_locked(path) {
+        return Stability::Locked;
+    }
+
+    let size = metadata.len();
+    let modified = metadata.modified().ok();
+    let now = Instant::now();
+
+    let mut observed = match OBSERVED.lock() {
+        Ok(o) => o,
+        Err(_) => return Stability::Changing,
+    };
+
+    match observed.get_mut(path) {
+        Some(obs) if obs.size == size && obs.modified == modified => {
+            if now.duration_since(obs.since) >= quiet_window {
+                Stability::Stable
+            } else {
+                Stability::Changing
+            }
+        }
+        Some(obs) => {
+            obs.size = size;
+            obs.modified = modified;
+            obs.since = now;
+            Stability::Changing
+        }
+        None => {
+            let idle = modified
+                .and_then(|m| SystemTime::now().duration_since(m).ok())
+                .unwrap_or_default();
+            observed.insert(path.to_path_buf(), Observation { size, modified, since: now });
+            if idle >= quiet_window {
+                Stability::Stable
+            } else {
+                Stability::Changing
+            }
+        }
+    }
+}
+
+/// 上传完成或文件删除后清除观察记录
+pub fn forget(path: &Path) {
+    if let Ok(mut observed) = OBSERVED.lock() {
+        observed.remove(path);
+    }
+}
+
+/// 编辑器在保存过程中留下的锁文件/临时文件
+fn has_editor_sibling(path: &Path) -> bool {
+    let (parent, name) = match (path.parent(), path.file_name().and_then(|n| n.to_str())) {
+        (Some(p), Some(n)) => (p, n),
+        _ => return false,
+    };
+
+    let mut candidates = vec![
+        format!("~${}", name),          // Office
+        format!(".~lock.{}#", name),    // LibreOffice
+        format!("{}@", name),           // Blender 保存时的临时文件
+        format!("{}.tmp", name),
+        format!("{}.lock", name),
+    ];
+    // Office 对长文件名只保留后半部分：~$ + 去掉前两个字符
+    let tail: String = name.chars().skip(2).collect();
+    if !tail.is_empty() {
+        candidates.push(format!("~${}", tail));
+    }
+
+    candidates.iter().any(|c| parent.join(c).exists())
+}
+
+#[cfg(target_os = "windows")]
+fn is_locked(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+
+    // 以不共享方式打开，其他程序仍持有句柄时会失败
+    match std::fs::OpenOptions::new().read(true).share_mode(0).open(path) {
+        Ok(_) => false,
+        Err(e) => matches!(e.raw_os_error(), Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION)),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_locked(_path: &Path) -> bool {
+    false
+}
@@ -1,10 +1,16 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 use serde::{Deserialize, Serialize};
+use crate::folder_template;
+use crate::project_reconcile;
+use crate::scanner;
+use crate::sync_engine;
+use crate::sync_roots;
 
 /// 同步规则
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SyncRule {
     /// 自动下载，不上传（客户文件）
     DownloadOnly,
@@ -14,49 +20,45 @@ pub enum SyncRule {
     ManualUpload,
 }
 
-/// 文件夹类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FolderConfig {
-    pub name: String,
-    pub rule: SyncRule,
+impl SyncRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncRule::DownloadOnly => "download_only",
+            SyncRule::Bidirectional => "bidirectional",
+            SyncRule::ManualUpload => "manual_upload",
+        }
+    }
 }
 
-/// 项目目录结构
-pub const PROJECT_FOLDERS: [(&str, &str); 3] = [
-    ("客户文件", "download_only"),
-    ("作品文件", "bidirectional"),
-    ("模型文件", "manual_upload"),
-];
+/// 暂缓同步的文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitingFile {
+    pub path: String,
+    pub reason: String,
+}
 
 /// 文件同步状态
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SyncStatus {
     pub is_syncing: bool,
     pub last_sync: Option<String>,
     pub pending_uploads: u32,
     pub pending_downloads: u32,
+    /// 等待写入完成的文件
+    pub waiting: Vec<WaitingFile>,
+    /// 因批量删除/修改暂停、等待确认的项目
+    pub paused_projects: Vec<String>,
 }
 
 /// 创建项目目录结构
 #[tauri::command]
 pub async fn create_project_folders(
+    app: AppHandle,
     work_dir: String,
     project_name: String,
 ) -> Result<Vec<String>, String> {
-    let mut normalized_project_name = project_name;
-    if !normalized_project_name.contains('/') && !normalized_project_name.contains('\\') {
-        if let Some(pos) = normalized_project_name.rfind('_') {
-            let (left, right) = normalized_project_name.split_at(pos);
-            let right = &right[1..];
-            if right.starts_with('Q') && right[1..].chars().all(|c| c.is_ascii_digit()) {
-                normalized_project_name = format!("{}_{}", right, left);
-            }
-        }
-        normalized_project_name = normalized_project_name
-            .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
-    }
-
-    let base_path = PathBuf::from(&work_dir).join(&normalized_project_name);
+    let normalized_project_name = normalize_project_name(&project_name);
+    let base_path = resolve_project_path(&app, &work_dir, &normalized_project_name);
     let mut created_paths = Vec::new();
 
     // 创建项目根目录
@@ -66,28 +68,20 @@ pub async fn create_project_folders(
         created_paths.push(base_path.to_string_lossy().to_string());
     }
 
-    // 创建子目录
-    for (folder_name, _rule) in PROJECT_FOLDERS.iter() {
-        let folder_path = base_path.join(folder_name);
-        if !folder_path.exists() {
-            std::fs::create_dir_all(&folder_path)
-                .map_err(|e| format!("创建目录 {} 失败: {}", folder_name, e))?;
-            created_paths.push(folder_path.to_string_lossy().to_string());
-        }
-    }
+    // 按模板创建子目录
+    created_paths.extend(folder_template::ensure_project_tree(&base_path)?);
 
     Ok(created_paths)
 }
 
 /// 获取同步规则
+/// folder_type: 文件夹名或项目内相对路径（如 作品文件/效果图）
 #[tauri::command]
 pub fn get_sync_rule(folder_type: String) -> String {
-    match folder_type.as_str() {
-        "客户文件" => "download_only".to_string(),
-        "作品文件" => "bidirectional".to_string(),
-        "模型文件" => "manual_upload".to_string(),
-        _ => "unknown".to_string(),
-    }
+    folder_template::current()
+        .rule_for(&folder_type)
+        .map(|rule| rule.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
 }
 
 /// 重命名下载的模型文件
@@ -115,29 +109,25 @@ pub fn rename_model_file(
 /// 检查文件是否需要同步
 #[tauri::command]
 pub fn should_sync_file(folder_type: String, is_upload: bool) -> bool {
-    match folder_type.as_str() {
-        "客户文件" => !is_upload, // 只下载
-        "作品文件" => true, // 双向同步
-        "模型文件" => false, // 不自动同步，需手动
-        _ => false,
+    match folder_template::current().rule_for(&folder_type) {
+        Some(SyncRule::DownloadOnly) => !is_upload, // 只下载
+        Some(SyncRule::Bidirectional) => true, // 双向同步
+        Some(SyncRule::ManualUpload) => false, // 不自动同步，需手动
+        None => false,
     }
 }
 
 /// 获取同步状态
 #[tauri::command]
 pub fn get_sync_status() -> SyncStatus {
-    SyncStatus {
-        is_syncing: false,
-        last_sync: None,
-        pending_uploads: 0,
-        pending_downloads: 0,
-    }
+    sync_engine::status()
 }
 
 /// 打开项目文件夹
-/// sub_folder: 可选，指定子文件夹（客户文件/作品文件/模型文件）
+/// sub_folder: 可选，指定子文件夹（模板中的文件夹名，如 客户文件/作品文件/模型文件）
 #[tauri::command]
 pub async fn open_project_folder(
+    app: AppHandle,
     work_dir: String,
     project_name: String,
     sub_folder: Option<String>,
@@ -146,22 +136,10 @@ pub async fn open_project_folder(
     log::info!("[OpenFolder] 入参: work_dir={:?}, project_name={:?}, sub_folder={:?}", 
         work_dir, project_name, sub_folder);
     
-    let mut normalized_project_name = project_name.clone();
-    if !normalized_project_name.contains('/') && !normalized_project_name.contains('\\') {
-        if let Some(pos) = normalized_project_name.rfind('_') {
-            let (left, right) = normalized_project_name.split_at(pos);
-            let right = &right[1..];
-            if right.starts_with('Q') && right[1..].chars().all(|c| c.is_ascii_digit()) {
-                normalized_project_name = format!("{}_{}", right, left);
-            }
-        }
-        normalized_project_name = normalized_project_name
-            .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
-    }
-    
+    let normalized_project_name = normalize_project_name(&project_name);
     log::info!("[OpenFolder] 规范化后 project_name={:?}", normalized_project_name);
 
-    let project_path = PathBuf::from(&work_dir).join(&normalized_project_name);
+    let project_path = resolve_project_path(&app, &work_dir, &normalized_project_name);
     log::info!("[OpenFolder] project_path={:?}", project_path);
     
     // 确保项目根目录存在
@@ -170,15 +148,8 @@ pub async fn open_project_folder(
             .map_err(|e| format!("创建项目目录失败: {}", e))?;
     }
     
-    // 自动创建三个标准子文件夹
-    let standard_folders = ["客户文件", "作品文件", "模型文件"];
-    for folder in &standard_folders {
-        let sub_path = project_path.join(folder);
-        if !sub_path.exists() {
-            std::fs::create_dir_all(&sub_path)
-                .map_err(|e| format!("创建子目录 {} 失败: {}", folder, e))?;
-        }
-    }
+    // 按模板自动创建标准子文件夹
+    folder_template::ensure_project_tree(&project_path)?;
     
     // 确定要打开的路径
     let mut path = project_path.clone();
@@ -226,7 +197,12 @@ pub fn project_folder_exists(
     work_dir: String,
     project_name: String,
 ) -> bool {
-    let mut normalized_project_name = project_name;
+    existing_project_path(&work_dir, &normalize_project_name(&project_name)).exists()
+}
+
+/// 规范化项目文件夹名称：`名称_Q编号` 转为 `Q编号_名称`，并替换非法字符（包含路径分隔符时保持原样）
+pub fn normalize_project_name(project_name: &str) -> String {
+    let mut normalized_project_name = project_name.to_string();
     if !normalized_project_name.contains('/') && !normalized_project_name.contains('\\') {
         if let Some(pos) = normalized_project_name.rfind('_') {
             let (left, right) = normalized_project_name.split_at(pos);
@@ -238,7 +214,63 @@ pub fn project_folder_exists(
         normalized_project_name = normalized_project_name
             .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
     }
+    normalized_project_name
+}
+
+/// 拆分为群文件夹名称和其下的相对路径
+fn split_group_folder(project_name: &str) -> (&str, &str) {
+    project_name
+        .split_once(['/', '\\'])
+        .unwrap_or((project_name, ""))
+}
 
-    let path = PathBuf::from(&work_dir).join(&normalized_project_name);
-    path.exists()
+/// 群文件夹所在根目录：工作目录中没有该项目时查找其他根目录
+fn project_root(work_dir: &Path, group_code: &str) -> PathBuf {
+    if !project_reconcile::find_group_folders(work_dir, group_code).is_empty() {
+        return work_dir.to_path_buf();
+    }
+    sync_roots::locate(group_code)
+        .and_then(|l| Path::new(&l.group.path).parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| work_dir.to_path_buf())
+}
+
+/// 按项目编号找到已有的项目路径（不做整理）；都不存在时返回当前名称的路径
+fn existing_project_path(work_dir: &str, project_name: &str) -> PathBuf {
+    let work_dir = Path::new(work_dir);
+    let (group_folder, rest) = split_group_folder(project_name);
+    let target = work_dir.join(project_name);
+    if target.exists() {
+        return target;
+    }
+    let group_code = match scanner::group_code_of(group_folder) {
+        Some(code) => code,
+        None => return target,
+    };
+    let root = project_root(work_dir, &group_code);
+    match project_reconcile::find_group_folders(&root, &group_code).into_iter().next() {
+        Some(folder) if rest.is_empty() => folder,
+        Some(folder) => folder.join(rest),
+        None => target,
+    }
+}
+
+/// 按项目编号整理群文件夹名称（重命名/合并旧名称文件夹）后返回项目路径
+///
+/// 同步进行中无法整理时沿用已有的旧名称文件夹，避免再创建一个。
+fn resolve_project_path(app: &AppHandle, work_dir: &str, project_name: &str) -> PathBuf {
+    let (group_folder, rest) = split_group_folder(project_name);
+    let group_code = match scanner::group_code_of(group_folder) {
+        Some(code) => code,
+        None => return PathBuf::from(work_dir).join(project_name),
+    };
+
+    let root = project_root(Path::new(work_dir), &group_code);
+    match project_reconcile::reconcile_and_notify(app, &root, group_folder) {
+        Ok(_) if rest.is_empty() => root.join(group_folder),
+        Ok(_) => root.join(group_folder).join(rest),
+        Err(e) => {
+            log::warn!("[ProjectReconcile] 暂不整理 {}: {}", group_folder, e);
+            existing_project_path(work_dir, project_name)
+        }
+    }
 }
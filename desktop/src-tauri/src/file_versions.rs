@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::commands;
+use crate::file_stability::{self, Stability};
+use crate::file_sync::SyncRule;
+use crate::folder_template;
+use crate::placeholder;
+use crate::scanner;
+use crate::sync_conflict;
+use crate::sync_engine;
+use crate::sync_ignore::SyncIgnore;
+use crate::sync_index::SyncIndex;
+use crate::sync_roots;
+
+const VERSIONS_DIR_NAME: &str = "versions";
+const OBJECTS_DIR_NAME: &str = "objects";
+const CATALOG_FILE_NAME: &str = "catalog.json";
+/// 恢复为副本时的文件名标记
+const RESTORE_COPY_LABEL: &str = "历史版本";
+/// 等待文件写入完成的最多检查次数
+const STABLE_RETRIES: u32 = 10;
+
+/// 本地版本保留策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionPolicy {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 每个文件最多保留的版本数，0 表示不限
+    #[serde(default = "default_max_versions")]
+    pub max_versions: u32,
+    /// 超过天数的版本自动清理（每个文件至少保留最新一个），0 表示不限
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u32,
+    /// 版本库总大小上限（MB），超出时从最旧的版本开始清理，0 表示不限
+    #[serde(default = "default_max_size_mb")]
+    pub max_size_mb: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_versions() -> u32 {
+    20
+}
+
+fn default_max_age_days() -> u32 {
+    30
+}
+
+fn default_max_size_mb() -> u64 {
+    5120
+}
+
+impl Default for VersionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            max_versions: default_max_versions(),
+            max_age_days: default_max_age_days(),
+            max_size_mb: default_max_size_mb(),
+        }
+    }
+}
+
+/// 文件的一个本地版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileVersion {
+    pub id: String,
+    /// 内容 SHA-256，同时是版本库中的对象名
+    pub hash: String,
+    pub size: u64,
+    /// 保存时文件的修改时间
+    pub modified_at: Option<i64>,
+    pub created_at: i64,
+}
+
+/// 版本目录：键为 `群编号/项目内相对路径`，项目文件夹改名后仍能找到历史版本
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VersionCatalog {
+    files: BTreeMap<String, Vec<FileVersion>>,
+}
+
+lazy_static::lazy_static! {
+    /// 同一时间只有一个任务读写版本目录
+    static ref CATALOG_LOCK: Mutex<()> = Mutex::new(());
+}
+
+fn versions_dir(root: &Path) -> PathBuf {
+    SyncIndex::meta_dir(root).join(VERSIONS_DIR_NAME)
+}
+
+fn object_path(root: &Path, hash: &str) -> PathBuf {
+    versions_dir(root).join(OBJECTS_DIR_NAME).join(&hash[..2]).join(hash)
+}
+
+fn load_catalog(root: &Path) -> Result<VersionCatalog, String> {
+    let path = versions_dir(root).join(CATALOG_FILE_NAME);
+    if !path.exists() {
+        return Ok(VersionCatalog::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取版本目录失败: {}", e))?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!("[FileVersions] 版本目录损坏，重新建立: {}", e);
+        VersionCatalog::default()
+    }))
+}
+
+fn save_catalog(root: &Path, catalog: &VersionCatalog) -> Result<(), String> {
+    let dir = versions_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let content = serde_json::to_string(catalog).map_err(|e| e.to_string())?;
+    let tmp = dir.join(format!("{}.tmp", CATALOG_FILE_NAME));
+    fs::write(&tmp, content).map_err(|e| format!("写入版本目录失败: {}", e))?;
+    fs::rename(&tmp, dir.join(CATALOG_FILE_NAME)).map_err(|e| format!("保存版本目录失败: {}", e))
+}
+
+fn policy() -> VersionPolicy {
+    sync_engine::config().map(|c| c.versions).unwrap_or_default()
+}
+
+/// 文件所在的根目录及版本目录键；不在同步根目录的项目内时返回 None
+fn locate_file(path: &Path) -> Option<(PathBuf, String, String)> {
+    let root = PathBuf::from(sync_roots::root_of(path)?.path);
+    let rel = path.strip_prefix(&root).ok()?;
+    let mut parts = rel.components().map(|c| match c {
+        Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+        _ => None,
+    });
+    let project = parts.next()??;
+    let group = scanner::parse_group_folder(&project, &root.join(&project))?;
+    let project_rel = parts.collect::<Option<Vec<_>>>()?.join("/");
+    if project_rel.is_empty() {
+        return None;
+    }
+    let key = format!("{}/{}", group.group_code, project_rel);
+    Some((root, key, project_rel))
+}
+
+/// 只为双向同步文件夹（作品文件）中的文件保留版本
+fn is_versioned(path: &Path, project_rel: &str) -> bool {
+    if folder_template::current().rule_for(project_rel) != Some(SyncRule::Bidirectional) {
+        return false;
+    }
+    if project_rel.split('/').any(|p| p.starts_with('.')) || placeholder::read(path).is_some() {
+        return false;
+    }
+    match path.parent() {
+        Some(parent) => !SyncIgnore::for_dir(parent).is_ignored(path, false),
+        None => false,
+    }
+}
+
+/// 将对象写入版本库，已存在时直接复用
+fn store_object(root: &Path, path: &Path, hash: &str) -> Result<(), String> {
+    let object = object_path(root, hash);
+    if object.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = object.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let tmp = object.with_extension("tmp");
+    fs::copy(path, &tmp).map_err(|e| format!("保存版本失败: {}", e))?;
+    // 复制期间文件被再次修改时丢弃，等下一次保存事件
+    if commands::sha256_file(&tmp)? != hash {
+        let _ = fs::remove_file(&tmp);
+        return Err("保存版本时文件已变化".to_string());
+    }
+    fs::rename(&tmp, &object).map_err(|e| format!("保存版本失败: {}", e))
+}
+
+/// 按策略清理一个文件的旧版本，每个文件至少保留最新一个
+fn apply_file_policy(versions: &mut Vec<FileVersion>, policy: &VersionPolicy, now: i64) {
+    versions.sort_by_key(|v| v.created_at);
+    if policy.max_versions > 0 {
+        let excess = versions.len().saturating_sub(policy.max_versions as usize);
+        versions.drain(..excess);
+    }
+    if policy.max_age_days > 0 {
+        let max_age = policy.max_age_days as i64 * 86400;
+        let latest = versions.len().saturating_sub(1);
+        let mut i = 0;
+        versions.retain(|v| {
+            let keep = i == latest || now - v.created_at <= max_age;
+            i += 1;
+            keep
+        });
+    }
+}
+
+/// 总大小超出上限时从最旧的版本开始清理（各文件最新版本除外），然后删除不再引用的对象
+fn collect_garbage(root: &Path, catalog: &mut VersionCatalog, policy: &VersionPolicy) -> u32 {
+    if policy.max_size_mb > 0 {
+        let max_size = policy.max_size_mb * 1024 * 1024;
+        let mut unique: BTreeMap<&str, u64> = BTreeMap::new();
+        for v in catalog.files.values().flatten() {
+            unique.insert(&v.hash, v.size);
+        }
+        let mut total: u64 = unique.values().sum();
+
+        if total > max_size {
+            let mut candidates: Vec<(i64, String, String)> = catalog
+                .files
+                .iter()
+                .flat_map(|(key, versions)| {
+                    let latest = versions.iter().map(|v| v.created_at).max();
+                    versions
+                        .iter()
+                        .filter(move |v| Some(v.created_at) != latest)
+                        .map(move |v| (v.created_at, key.clone(), v.id.clone()))
+                })
+                .collect();
+            candidates.sort();
+
+            for (_, key, id) in candidates {
+                if total <= max_size {
+                    break;
+                }
+                if let Some(versions) = catalog.files.get_mut(&key) {
+                    if let Some(pos) = versions.iter().position(|v| v.id == id) {
+                        let removed = versions.remove(pos);
+                        let still_used = catalog.files.values().flatten().any(|v| v.hash == removed.hash);
+                        if !still_used {
+                            total = total.saturating_sub(removed.size);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let referenced: HashSet<&str> = catalog.files.values().flatten().map(|v| v.hash.as_str()).collect();
+    let objects_dir = versions_dir(root).join(OBJECTS_DIR_NAME);
+    let mut removed = 0;
+    for entry in walkdir::WalkDir::new(&objects_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if !referenced.contains(name.as_ref()) && fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// 为文件保存一个版本，内容与最新版本相同时跳过；返回是否新建了版本
+pub fn snapshot(path: &Path) -> Result<bool, String> {
+    let policy = policy();
+    if !policy.enabled || !path.is_file() {
+        return Ok(false);
+    }
+    let (root, key, project_rel) = match locate_file(path) {
+        Some(found) => found,
+        None => return Ok(false),
+    };
+    if !is_versioned(path, &project_rel) {
+        return Ok(false);
+    }
+
+    let metadata = fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+    let hash = commands::sha256_file(path)?;
+
+    let _lock = CATALOG_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut catalog = load_catalog(&root)?;
+    let versions = catalog.files.entry(key.clone()).or_default();
+    if versions.iter().max_by_key(|v| v.created_at).is_some_and(|v| v.hash == hash) {
+        return Ok(false);
+    }
+
+    store_object(&root, path, &hash)?;
+    let now = chrono::Local::now();
+    versions.push(FileVersion {
+        id: format!("{}-{}", now.format("%Y%m%d%H%M%S%3f"), &hash[..8]),
+        hash,
+        size: metadata.len(),
+        modified_at: metadata
+            .modified()
+            .ok()
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp()),
+        created_at: now.timestamp(),
+    });
+    apply_file_policy(versions, &policy, now.timestamp());
+    collect_garbage(&root, &mut catalog, &policy);
+    save_catalog(&root, &catalog)?;
+
+    log::info!("[FileVersions] 已保存版本 {}", key);
+    Ok(true)
+}
+
+/// 文件写入完成后保存版本，写入中的文件稍后重试
+pub fn snapshot_when_stable(paths: Vec<PathBuf>) {
+    let mut pending = paths;
+    for _ in 0..STABLE_RETRIES {
+        pending.retain(|path| {
+            match file_stability::check(path, Duration::from_secs(2)) {
+                Stability::Stable => {}
+                _ if path.exists() => return true,
+                _ => return false,
+            }
+            if let Err(e) = snapshot(path) {
+                log::warn!("[FileVersions] 保存 {:?} 的版本失败: {}", path, e);
+            }
+            false
+        });
+        if pending.is_empty() {
+            return;
+        }
+        std::thread::sleep(Duration::from_secs(3));
+    }
+    log::warn!("[FileVersions] {} 个文件持续写入中，本次未保存版本", pending.len());
+}
+
+/// 列出文件的本地版本（最新的在前）
+#[tauri::command]
+pub async fn list_file_versions(file_path: String) -> Result<Vec<FileVersion>, String> {
+    tokio::task::spawn_blocking(move || list_blocking(&file_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn list_blocking(file_path: &str) -> Result<Vec<FileVersion>, String> {
+    let (root, key, _) = locate_file(Path::new(&file_path)).ok_or("文件不在同步根目录的项目中")?;
+    let _lock = CATALOG_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut versions = load_catalog(&root)?.files.remove(&key).unwrap_or_default();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.created_at));
+    Ok(versions)
+}
+
+/// 恢复文件的某个版本，返回恢复后的路径
+///
+/// 覆盖前先为当前内容保存版本，恢复操作本身也可撤销；as_copy 为 true 时恢复为副本，不改动原文件。
+#[tauri::command]
+pub async fn restore_file_version(file_path: String, version_id: String, as_copy: Option<bool>) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || restore_blocking(Path::new(&file_path), &version_id, as_copy.unwrap_or(false)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn restore_blocking(path: &Path, version_id: &str, as_copy: bool) -> Result<String, String> {
+    let (root, key, _) = locate_file(path).ok_or("文件不在同步根目录的项目中")?;
+
+    let version = {
+        let _lock = CATALOG_LOCK.lock().map_err(|e| e.to_string())?;
+        load_catalog(&root)?
+            .files
+            .remove(&key)
+            .and_then(|versions| versions.into_iter().find(|v| v.id == version_id))
+            .ok_or("版本不存在")?
+    };
+    let object = object_path(&root, &version.hash);
+    if commands::sha256_file(&object).ok().as_deref() != Some(version.hash.as_str()) {
+        return Err("版本文件已损坏或被清理".to_string());
+    }
+
+    let target = if as_copy || !path.exists() {
+        if path.exists() {
+            sync_conflict::conflict_copy_path(path, RESTORE_COPY_LABEL)
+        } else {
+            path.to_path_buf()
+        }
+    } else {
+        if let Err(e) = snapshot(path) {
+            log::warn!("[FileVersions] 恢复前保存当前版本失败: {}", e);
+        }
+        path.to_path_buf()
+    };
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let tmp = target.with_file_name(format!(
+        ".{}.restoring",
+        target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    ));
+    fs::copy(&object, &tmp).map_err(|e| format!("恢复版本失败: {}", e))?;
+    fs::rename(&tmp, &target).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("恢复版本失败，文件可能正被占用: {}", e)
+    })?;
+
+    log::info!("[FileVersions] {} 已恢复到版本 {}", key, version.id);
+    Ok(target.to_string_lossy().to_string())
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use crate::file_sync::SyncRule;
+
+/// 模板配置文件名（位于应用配置目录）
+pub const TEMPLATE_FILE_NAME: &str = "folder_templates.json";
+
+/// 预置文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedFile {
+    pub name: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+/// 项目文件夹模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderTemplate {
+    pub name: String,
+    /// 对应服务端的资源类型（works/models/customer），仅顶层文件夹使用
+    #[serde(default)]
+    pub asset_type: Option<String>,
+    /// 同步规则，子文件夹未配置时继承上级
+    #[serde(default)]
+    pub rule: Option<SyncRule>,
+    #[serde(default)]
+    pub subfolders: Vec<FolderTemplate>,
+    #[serde(default)]
+    pub seed_files: Vec<SeedFile>,
+}
+
+/// 项目文件夹模板配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderTemplateConfig {
+    pub folders: Vec<FolderTemplate>,
+}
+
+impl Default for FolderTemplateConfig {
+    fn default() -> Self {
+        Self {
+            folders: vec![
+                FolderTemplate {
+                    name: "客户文件".to_string(),
+                    asset_type: Some("customer".to_string()),
+                    rule: Some(SyncRule::DownloadOnly),
+                    subfolders: Vec::new(),
+                    seed_files: Vec::new(),
+                },
+                FolderTemplate {
+                    name: "作品文件".to_string(),
+                    asset_type: Some("works".to_string()),
+                    rule: Some(SyncRule::Bidirectional),
+                    subfolders: Vec::new(),
+                    seed_files: Vec::new(),
+                },
+                FolderTemplate {
+                    name: "模型文件".to_string(),
+                    asset_type: Some("models".to_string()),
+                    rule: Some(SyncRule::ManualUpload),
+                    subfolders: Vec::new(),
+                    seed_files: Vec::new(),
+                },
+            ],
+        }
+    }
+}
+
+impl FolderTemplateConfig {
+    /// 校验模板：名称合法且不重复，顶层文件夹必须配置同步规则
+    pub fn validate(&self) -> Result<(), String> {
+        if self.folders.is_empty() {
+            return Err("至少需要一个项目文件夹".to_string());
+        }
+
+        let mut asset_types = HashSet::new();
+        for folder in &self.folders {
+            if folder.rule.is_none() {
+                return Err(format!("文件夹 {} 未配置同步规则", folder.name));
+            }
+            if let Some(asset_type) = &folder.asset_type {
+                if !asset_types.insert(asset_type.as_str()) {
+                    return Err(format!("资源类型 {} 重复", asset_type));
+                }
+            }
+        }
+
+        validate_level(&self.folders)
+    }
+
+    /// 按资源类型或文件夹名查找顶层模板
+    pub fn find(&self, asset_type_or_name: &str) -> Option<&FolderTemplate> {
+        self.folders.iter().find(|f| {
+            f.asset_type.as_deref() == Some(asset_type_or_name) || f.name == asset_type_or_name
+        })
+    }
+
+    /// 解析项目内相对路径（如 `作品文件/效果图/a.png`）的同步规则，取最深一层配置
+    pub fn rule_for(&self, rel_path: &str) -> Option<SyncRule> {
+        let mut parts = rel_path.split(['/', '\\']).filter(|p| !p.is_empty());
+        let mut current = self.folders.iter().find(|f| Some(f.name.as_str()) == parts.next())?;
+        let mut rule = current.rule;
+
+        for part in parts {
+            match current.subfolders.iter().find(|f| f.name == part) {
+                Some(sub) => {
+                    current = sub;
+                    if sub.rule.is_some() {
+                        rule = sub.rule;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        rule
+    }
+}
+
+fn validate_level(folders: &[FolderTemplate]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for folder in folders {
+        validate_name(&folder.name)?;
+        if !names.insert(folder.name.as_str()) {
+            return Err(format!("文件夹 {} 重复", folder.name));
+        }
+        for seed in &folder.seed_files {
+            validate_name(&seed.name)?;
+        }
+        validate_level(&folder.subfolders)?;
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name == "." || name == ".." {
+        return Err("名称不能为空".to_string());
+    }
+    if name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|']) {
+        return Err(format!("名称 {} 包含非法字符", name));
+    }
+    Ok(())
+}
+
+lazy_static::lazy_static! {
+    static ref TEMPLATES: RwLock<Arc<FolderTemplateConfig>> = RwLock::new(Arc::new(FolderTemplateConfig::default()));
+    static ref TEMPLATE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// 启动时加载模板配置，文件不存在或无效时使用默认模板
+pub fn init(config_dir: PathBuf) {
+    let path = config_dir.join(TEMPLATE_FILE_NAME);
+    if let Ok(mut p) = TEMPLATE_PATH.write() {
+        *p = Some(path.clone());
+    }
+
+    match load_from(&path) {
+        Ok(Some(config)) => {
+            if let Ok(mut t) = TEMPLATES.write() {
+                *t = Arc::new(config);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("[FolderTemplate] 加载模板失败，使用默认模板: {}", e),
+    }
+}
+
+fn load_from(path: &Path) -> Result<Option<FolderTemplateConfig>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("读取模板失败: {}", e))?;
+    let config: FolderTemplateConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析模板失败: {}", e))?;
+    config.validate()?;
+    Ok(Some(config))
+}
+
+/// 当前模板配置（共享只读，保存或重新加载时整体替换）
+pub fn current() -> Arc<FolderTemplateConfig> {
+    TEMPLATES.read().map(|t| Arc::clone(&t)).unwrap_or_default()
+}
+
+/// 按模板创建项目目录树（含子文件夹与预置文件），返回新建的路径
+pub fn ensure_project_tree(project_path: &Path) -> Result<Vec<String>, String> {
+    let mut created = Vec::new();
+    ensure_level(project_path, &current().folders, &mut created)?;
+    Ok(created)
+}
+
+fn ensure_level(base: &Path, folders: &[FolderTemplate], created: &mut Vec<String>) -> Result<(), String> {
+    for folder in folders {
+        let folder_path = base.join(&folder.name);
+        if !folder_path.exists() {
+            fs::create_dir_all(&folder_path)
+                .map_err(|e| format!("创建目录 {} 失败: {}", folder.name, e))?;
+            created.push(folder_path.to_string_lossy().to_string());
+        }
+
+        for seed in &folder.seed_files {
+            let seed_path = folder_path.join(&seed.name);
+            if !seed_path.exists() {
+                fs::write(&seed_path, &seed.content)
+                    .map_err(|e| format!("创建文件 {} 失败: {}", seed.name, e))?;
+                created.push(seed_path.to_string_lossy().to_string());
+            }
+        }
+
+        ensure_level(&folder_path, &folder.subfolders, created)?;
+    }
+    Ok(())
+}
+
+/// 获取项目文件夹模板
+#[tauri::command]
+pub fn get_folder_templates() -> FolderTemplateConfig {
+    current().as_ref().clone()
+}
+
+/// 保存项目文件夹模板
+#[tauri::command]
+pub fn save_folder_templates(config: FolderTemplateConfig) -> Result<(), String> {
+    config.validate()?;
+
+    let path = TEMPLATE_PATH
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("模板配置路径未初始化")?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("保存模板失败: {}", e))?;
+
+    *TEMPLATES.write().map_err(|e| e.to_string())? = Arc::new(config);
+    Ok(())
+}
+
+/// 从配置文件重新加载模板
+#[tauri::command]
+pub fn reload_folder_templates() -> Result<FolderTemplateConfig, String> {
+    let path = TEMPLATE_PATH
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("模板配置路径未初始化")?;
+
+    let config = load_from(&path)?.unwrap_or_default();
+    *TEMPLATES.write().map_err(|e| e.to_string())? = Arc::new(config.clone());
+    Ok(config)
+}
@@ -1,10 +1,38 @@
+mod chunker;
 mod commands;
+mod delta_sync;
+mod download_cache;
 mod downloader;
+mod placeholder;
+mod project_archive;
+mod project_reconcile;
+mod rate_limit;
+mod s3_etag;
 mod scanner;
+mod scan_stream;
+mod selective_sync;
+mod file_hash;
+mod file_stability;
+mod file_versions;
+mod sync_client;
+mod sync_conflict;
+mod sync_engine;
+mod sync_guard;
+mod sync_ignore;
+mod sync_index;
+mod sync_log;
+mod sync_planner;
+mod sync_queue;
+mod sync_rename;
+mod sync_roots;
+mod sync_scheduler;
+mod sync_trash;
+mod sync_watcher;
 mod uploader;
 mod clipboard;
 mod keyboard;
 mod file_sync;
+mod folder_template;
 mod mouse_listener;
 mod tray_badge;
 mod window_control;
@@ -45,6 +73,27 @@ pub fn run() {
                 .build(),
         )
         .setup(|app| {
+            // 加载项目文件夹模板、选择性同步与根目录设置
+            match app.path().app_config_dir() {
+                Ok(dir) => {
+                    folder_template::init(dir.clone());
+                    selective_sync::init(dir.clone());
+                    sync_roots::init(dir);
+                }
+                Err(e) => log::warn!("获取配置目录失败，使用默认文件夹模板: {}", e),
+            }
+            match app.path().app_data_dir() {
+                Ok(dir) => sync_log::init(dir),
+                Err(e) => log::warn!("获取数据目录失败，不记录同步日志: {}", e),
+            }
+            match app.path().app_cache_dir() {
+                Ok(dir) => download_cache::init(dir),
+                Err(e) => log::warn!("获取缓存目录失败，不使用下载缓存: {}", e),
+            }
+
+            // 离线操作队列：服务器恢复可达后自动重放
+            sync_queue::start(app.handle().clone());
+
             #[cfg(desktop)]
             {
                 use tauri::tray::{TrayIconBuilder, MouseButton, MouseButtonState, TrayIconEvent};
@@ -126,13 +175,19 @@ pub fn run() {
             commands::scan_root_directory,
             commands::list_dir_entries,
             commands::scan_folder_recursive,
+            scan_stream::scan_folder_stream,
+            scan_stream::cancel_folder_scan,
             commands::get_local_files,
-            commands::calculate_file_hash,
+            file_hash::calculate_file_hash,
+            file_hash::calculate_file_hash_batch,
+            s3_etag::compute_s3_etag,
             commands::get_file_metadata,
+            commands::get_file_metadata_batch,
             commands::read_file_chunk,
             commands::write_file_chunk,
             commands::ensure_directory,
             commands::get_mime_type,
+            sync_ignore::is_ignored,
             downloader::download_file,
             downloader::download_file_chunked,
             downloader::open_file_location,
@@ -155,6 +210,47 @@ pub fn run() {
             file_sync::get_sync_status,
             file_sync::open_project_folder,
             file_sync::project_folder_exists,
+            folder_template::get_folder_templates,
+            folder_template::save_folder_templates,
+            folder_template::reload_folder_templates,
+            sync_engine::configure_sync_engine,
+            sync_engine::reset_sync_engine,
+            sync_engine::run_sync,
+            sync_guard::get_paused_projects,
+            sync_guard::confirm_sync_changes,
+            sync_trash::list_trash,
+            sync_trash::restore_from_trash,
+            sync_trash::purge_trash,
+            sync_conflict::list_conflicts,
+            sync_conflict::resolve_conflict,
+            selective_sync::get_selective_sync,
+            selective_sync::save_selective_sync,
+            selective_sync::set_project_sync_mode,
+            selective_sync::get_project_listing,
+            selective_sync::evict_project,
+            sync_scheduler::start_sync_scheduler,
+            sync_scheduler::stop_sync_scheduler,
+            sync_scheduler::trigger_sync_now,
+            sync_scheduler::get_sync_scheduler_status,
+            sync_queue::enqueue_sync_operation,
+            sync_queue::get_sync_queue,
+            sync_queue::retry_failed_operations,
+            sync_watcher::start_file_watcher,
+            sync_watcher::stop_file_watcher,
+            sync_log::query_sync_log,
+            sync_log::export_sync_log_csv,
+            sync_roots::get_sync_roots,
+            sync_roots::save_sync_roots,
+            sync_roots::find_duplicate_projects,
+            sync_roots::locate_project,
+            project_reconcile::reconcile_project_folders,
+            project_archive::archive_project,
+            project_archive::restore_archived_project,
+            file_versions::list_file_versions,
+            file_versions::restore_file_version,
+            placeholder::hydrate_placeholder,
+            download_cache::get_download_cache_stats,
+            download_cache::clear_download_cache,
             mouse_listener::save_mouse_position,
             mouse_listener::get_saved_position,
             mouse_listener::click_saved_position,
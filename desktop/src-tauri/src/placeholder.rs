@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::commands;
+use crate::delta_sync;
+use crate::download_cache;
+use crate::sync_client::{RemoteResource, SyncClient};
+use crate::sync_engine;
+use crate::sync_roots;
+
+/// 占位文件后缀：`效果图.psd` 的占位文件为 `效果图.psd.cloud`
+pub const PLACEHOLDER_SUFFIX: &str = ".cloud";
+/// 占位文件内容标识
+const PLACEHOLDER_KIND: &str = "cloud-placeholder";
+/// 超过此大小的文件不可能是占位文件，不读取内容
+const MAX_PLACEHOLDER_SIZE: u64 = 64 * 1024;
+
+/// 占位文件内容：服务端文件的元数据，打开时据此下载
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Placeholder {
+    pub kind: String,
+    pub group_code: String,
+    pub asset_type: String,
+    #[serde(flatten)]
+    pub remote: RemoteResource,
+}
+
+impl Placeholder {
+    pub fn new(group_code: &str, asset_type: &str, remote: &RemoteResource) -> Self {
+        Self {
+            kind: PLACEHOLDER_KIND.to_string(),
+            group_code: group_code.to_string(),
+            asset_type: asset_type.to_string(),
+            remote: remote.clone(),
+        }
+    }
+}
+
+/// 真实文件对应的占位文件路径
+pub fn placeholder_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(PLACEHOLDER_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// 占位文件对应的真实文件路径，不是占位文件名时返回 None
+pub fn real_path(placeholder: &Path) -> Option<PathBuf> {
+    let name = placeholder.file_name()?.to_str()?;
+    let real = name.strip_suffix(PLACEHOLDER_SUFFIX).filter(|n| !n.is_empty())?;
+    Some(placeholder.with_file_name(real))
+}
+
+/// 读取占位文件，文件名或内容不符时返回 None
+pub fn read(path: &Path) -> Option<Placeholder> {
+    real_path(path)?;
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_PLACEHOLDER_SIZE {
+        return None;
+    }
+    let placeholder: Placeholder = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    (placeholder.kind == PLACEHOLDER_KIND).then_some(placeholder)
+}
+
+/// 为 path 处的服务端文件创建或更新占位文件
+pub fn write(path: &Path, placeholder: &Placeholder) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(placeholder).map_err(|e| e.to_string())?;
+    fs::write(placeholder_path(path), content).map_err(|e| format!("写入占位文件失败: {}", e))
+}
+
+/// 删除 path 的占位文件（服务端已删除）
+pub fn remove(path: &Path) -> Result<(), String> {
+    let placeholder = placeholder_path(path);
+    if placeholder.exists() {
+        fs::remove_file(&placeholder).map_err(|e| format!("删除占位文件失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 查找路径对应的占位文件：本身是占位文件，或真实文件不存在而占位文件存在
+pub fn find(path: &Path) -> Option<PathBuf> {
+    if read(path).is_some() {
+        return Some(path.to_path_buf());
+    }
+    if path.exists() {
+        return None;
+    }
+    let placeholder = placeholder_path(path);
+    read(&placeholder).map(|_| placeholder)
+}
+
+/// 下载占位文件的真实内容，完成后删除占位文件，返回真实文件路径
+///
+/// 不写入同步索引：下次同步时两端一致，按普通文件记录。
+pub async fn hydrate(placeholder_file: &Path) -> Result<PathBuf, String> {
+    let placeholder = read(placeholder_file).ok_or("不是有效的占位文件")?;
+    let path = real_path(placeholder_file).ok_or("不是有效的占位文件")?;
+    if path.exists() {
+        // 已由其他途径下载
+        let _ = fs::remove_file(placeholder_file);
+        return Ok(path);
+    }
+
+    let config = sync_engine::config().ok_or("同步引擎未配置")?;
+    let root = sync_roots::root_of(&path).ok_or("文件不在同步根目录中")?;
+    let client = SyncClient::new(&config.server_url, config.token.clone()).with_limits(&root);
+    let remote = &placeholder.remote;
+
+    let (part_path, _) = delta_sync::download_file(&client, remote, &path, None).await?;
+
+    let size = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+    let hash_mismatch = remote
+        .hash
+        .as_ref()
+        .is_some_and(|h| commands::sha256_file(&part_path).ok().as_deref() != Some(h.as_str()));
+    if size != remote.size || hash_mismatch {
+        let _ = fs::remove_file(&part_path);
+        return Err("下载的文件与服务端记录不一致".to_string());
+    }
+
+    fs::rename(&part_path, &path).map_err(|e| format!("保存文件失败: {}", e))?;
+    download_cache::store(remote, &path).await;
+    if let Err(e) = fs::remove_file(placeholder_file) {
+        log::warn!("[Placeholder] 删除占位文件 {:?} 失败: {}", placeholder_file, e);
+    }
+    log::info!("[Placeholder] 已下载 {}/{}/{}", placeholder.group_code, placeholder.asset_type, remote.rel_path);
+    Ok(path)
+}
+
+/// 下载占位文件的真实内容，file_path 可以是占位文件或其真实文件路径，返回真实文件路径
+#[tauri::command]
+pub async fn hydrate_placeholder(file_path: String) -> Result<String, String> {
+    let placeholder = find(Path::new(&file_path)).ok_or("不是占位文件")?;
+    hydrate(&placeholder).await.map(|p| p.to_string_lossy().to_string())
+}
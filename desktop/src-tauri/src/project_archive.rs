@@ -0,0 +1,611 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+use crate::folder_template;
+use crate::sync_conflict;
+use crate::sync_engine;
+use crate::sync_index::SyncIndex;
+use crate::sync_roots;
+
+/// 归档内的清单文件
+const MANIFEST_NAME: &str = "manifest.json";
+/// 归档后项目文件夹中保留的占位标记
+const MARKER_FILE_NAME: &str = ".archived.json";
+/// 占位说明文件
+const README_FILE_NAME: &str = "项目已归档.txt";
+/// 恢复时本地已有同名文件，归档中的版本以副本保留
+const RESTORE_COPY_LABEL: &str = "归档版本";
+
+/// 归档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    #[default]
+    Zip,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+
+    fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.zst") {
+            Some(ArchiveFormat::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+/// 清单中的文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// 相对项目文件夹的路径，以 / 分隔
+    pub path: String,
+    pub size: u64,
+    pub modified_at: Option<String>,
+    pub sha256: String,
+}
+
+/// 归档清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub group_code: String,
+    pub group_name: String,
+    pub folder_name: String,
+    pub archived_at: String,
+    pub format: ArchiveFormat,
+    pub total_size: u64,
+    pub files: Vec<ManifestFile>,
+}
+
+/// 占位文件夹中的归档标记
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMarker {
+    pub archive_path: String,
+    pub archived_at: String,
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+/// 归档结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveResult {
+    pub archive_path: String,
+    pub file_count: usize,
+    pub total_size: u64,
+    pub archive_size: u64,
+    /// 是否已删除本地文件、只保留占位文件夹
+    pub removed_local: bool,
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub group_code: String,
+    pub path: String,
+    pub restored_files: usize,
+    /// 本地已存在、归档版本以副本保留的文件
+    pub copies: Vec<String>,
+}
+
+/// 归档进度事件
+#[derive(Debug, Clone, Serialize)]
+struct ArchiveProgress {
+    group_code: String,
+    /// packing / verifying / restoring
+    stage: &'static str,
+    processed: usize,
+    total: usize,
+}
+
+fn emit_progress(app: &AppHandle, group_code: &str, stage: &'static str, processed: usize, total: usize) {
+    let _ = app.emit(
+        "archive-progress",
+        ArchiveProgress { group_code: group_code.to_string(), stage, processed, total },
+    );
+}
+
+/// 项目文件夹是否已归档（只剩占位）
+pub fn is_archived(project_dir: &Path) -> bool {
+    project_dir.join(MARKER_FILE_NAME).is_file()
+}
+
+fn read_marker(project_dir: &Path) -> Option<ArchiveMarker> {
+    let content = fs::read_to_string(project_dir.join(MARKER_FILE_NAME)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 边读边计算 SHA-256，可同时写入目标
+fn copy_hashed(reader: &mut dyn Read, mut writer: Option<&mut dyn Write>) -> io::Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut total = 0u64;
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        if let Some(w) = writer.as_mut() {
+            w.write_all(&buffer[..n])?;
+        }
+        total += n as u64;
+    }
+    Ok((hex::encode(hasher.finalize()), total))
+}
+
+/// 生成清单，跳过占位文件
+fn build_manifest(
+    project_dir: &Path,
+    group_code: &str,
+    group_name: &str,
+    format: ArchiveFormat,
+) -> Result<ArchiveManifest, String> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(project_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = match entry.path().strip_prefix(project_dir) {
+            Ok(r) => r.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        if rel == MARKER_FILE_NAME || rel == README_FILE_NAME {
+            continue;
+        }
+        let metadata = entry.metadata().map_err(|e| format!("读取文件信息失败 {}: {}", rel, e))?;
+        let mut file = File::open(entry.path()).map_err(|e| format!("无法打开文件 {}: {}", rel, e))?;
+        let (sha256, _) = copy_hashed(&mut file, None).map_err(|e| format!("读取文件失败 {}: {}", rel, e))?;
+        files.push(ManifestFile {
+            path: rel,
+            size: metadata.len(),
+            modified_at: metadata
+                .modified()
+                .ok()
+                .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339()),
+            sha256,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(ArchiveManifest {
+        group_code: group_code.to_string(),
+        group_name: group_name.to_string(),
+        folder_name: project_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        archived_at: chrono::Local::now().to_rfc3339(),
+        format,
+        total_size: files.iter().map(|f| f.size).sum(),
+        files,
+    })
+}
+
+fn write_zip(
+    path: &Path,
+    project_dir: &Path,
+    manifest: &ArchiveManifest,
+    progress: &dyn Fn(usize),
+) -> Result<(), String> {
+    use zip::write::SimpleFileOptions;
+
+    let file = File::create(path).map_err(|e| format!("创建归档失败: {}", e))?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(file));
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    let json = serde_json::to_vec_pretty(manifest).map_err(|e| e.to_string())?;
+    zip.start_file(MANIFEST_NAME, options).map_err(|e| e.to_string())?;
+    zip.write_all(&json).map_err(|e| format!("写入归档失败: {}", e))?;
+
+    for (i, f) in manifest.files.iter().enumerate() {
+        zip.start_file(format!("{}/{}", manifest.folder_name, f.path), options)
+            .map_err(|e| e.to_string())?;
+        let mut src = File::open(project_dir.join(&f.path)).map_err(|e| format!("无法打开文件 {}: {}", f.path, e))?;
+        io::copy(&mut src, &mut zip).map_err(|e| format!("写入归档失败 {}: {}", f.path, e))?;
+        progress(i + 1);
+    }
+
+    zip.finish()
+        .map_err(|e| e.to_string())?
+        .flush()
+        .map_err(|e| format!("写入归档失败: {}", e))
+}
+
+fn write_tar_zst(
+    path: &Path,
+    project_dir: &Path,
+    manifest: &ArchiveManifest,
+    progress: &dyn Fn(usize),
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("创建归档失败: {}", e))?;
+    let encoder = zstd::Encoder::new(BufWriter::new(file), 3).map_err(|e| e.to_string())?;
+    let mut builder = tar::Builder::new(encoder);
+
+    let json = serde_json::to_vec_pretty(manifest).map_err(|e| e.to_string())?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, MANIFEST_NAME, json.as_slice())
+        .map_err(|e| format!("写入归档失败: {}", e))?;
+
+    for (i, f) in manifest.files.iter().enumerate() {
+        builder
+            .append_path_with_name(project_dir.join(&f.path), format!("{}/{}", manifest.folder_name, f.path))
+            .map_err(|e| format!("写入归档失败 {}: {}", f.path, e))?;
+        progress(i + 1);
+    }
+
+    let encoder = builder.into_inner().map_err(|e| format!("写入归档失败: {}", e))?;
+    encoder
+        .finish()
+        .and_then(|mut w| w.flush())
+        .map_err(|e| format!("写入归档失败: {}", e))
+}
+
+/// 依次读取归档中的文件，回调返回 false 时停止
+fn for_each_entry(
+    path: &Path,
+    mut f: impl FnMut(&str, &mut dyn Read) -> Result<bool, String>,
+) -> Result<(), String> {
+    let format = ArchiveFormat::detect(path).ok_or("不支持的归档格式")?;
+    let file = File::open(path).map_err(|e| format!("无法打开归档: {}", e))?;
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("读取归档失败: {}", e))?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i).map_err(|e| format!("读取归档失败: {}", e))?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                if !f(&name, &mut entry)? {
+                    break;
+                }
+            }
+        }
+        ArchiveFormat::TarZst => {
+            let decoder = zstd::Decoder::new(file).map_err(|e| format!("读取归档失败: {}", e))?;
+            let mut archive = tar::Archive::new(decoder);
+            for entry in archive.entries().map_err(|e| format!("读取归档失败: {}", e))? {
+                let mut entry = entry.map_err(|e| format!("读取归档失败: {}", e))?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry
+                    .path()
+                    .map_err(|e| format!("读取归档失败: {}", e))?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                if !f(&name, &mut entry)? {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 读取归档中的清单（写入时位于第一个条目）
+pub fn read_manifest(path: &Path) -> Result<ArchiveManifest, String> {
+    let mut manifest = None;
+    for_each_entry(path, |name, reader| {
+        if name != MANIFEST_NAME {
+            return Ok(true);
+        }
+        let mut content = String::new();
+        reader.read_to_string(&mut content).map_err(|e| format!("读取清单失败: {}", e))?;
+        manifest = Some(serde_json::from_str::<ArchiveManifest>(&content).map_err(|e| format!("清单格式错误: {}", e))?);
+        Ok(false)
+    })?;
+    manifest.ok_or_else(|| "归档中没有清单".to_string())
+}
+
+/// 条目名称对应的项目内相对路径；拒绝跳出项目文件夹的路径
+fn entry_rel_path<'a>(manifest: &ArchiveManifest, name: &'a str) -> Option<&'a str> {
+    let rel = name.strip_prefix(&manifest.folder_name)?.strip_prefix('/')?;
+    let safe = Path::new(rel).components().all(|c| matches!(c, Component::Normal(_)));
+    (safe && !rel.is_empty()).then_some(rel)
+}
+
+/// 逐个校验归档中的文件与清单一致
+fn verify(path: &Path, manifest: &ArchiveManifest, progress: &dyn Fn(usize)) -> Result<(), String> {
+    let expected: HashMap<&str, &ManifestFile> = manifest.files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let mut verified = 0;
+
+    for_each_entry(path, |name, reader| {
+        if name == MANIFEST_NAME {
+            return Ok(true);
+        }
+        let file = entry_rel_path(manifest, name)
+            .and_then(|rel| expected.get(rel))
+            .ok_or_else(|| format!("归档中有清单外的文件: {}", name))?;
+        let (hash, size) = copy_hashed(reader, None).map_err(|e| format!("读取归档失败 {}: {}", name, e))?;
+        if hash != file.sha256 || size != file.size {
+            return Err(format!("归档校验失败: {}", file.path));
+        }
+        verified += 1;
+        progress(verified);
+        Ok(true)
+    })?;
+
+    if verified != manifest.files.len() {
+        return Err(format!("归档校验失败: 应有 {} 个文件，实际 {} 个", manifest.files.len(), verified));
+    }
+    Ok(())
+}
+
+/// 清除项目在索引中的记录，避免占位文件夹被当作本地删除同步到服务端
+fn forget_group(root: &Path, group_code: &str) -> Result<(), String> {
+    let mut index = SyncIndex::load(root)?;
+    for template in &folder_template::current().folders {
+        let asset_type = match &template.asset_type {
+            Some(t) => t,
+            None => continue,
+        };
+        for rel in index.entries_under(group_code, asset_type).into_keys() {
+            let key = SyncIndex::key(group_code, asset_type, &rel);
+            index.remove(&key);
+            index.remove_conflicts_for(&key);
+        }
+    }
+    index.save()
+}
+
+/// 删除项目文件，只保留带归档标记和说明的占位文件夹
+fn replace_with_placeholder(project_dir: &Path, marker: &ArchiveMarker) -> Result<(), String> {
+    fs::remove_dir_all(project_dir).map_err(|e| format!("删除本地项目文件夹失败: {}", e))?;
+    fs::create_dir_all(project_dir).map_err(|e| format!("创建占位文件夹失败: {}", e))?;
+
+    let content = serde_json::to_string_pretty(marker).map_err(|e| e.to_string())?;
+    fs::write(project_dir.join(MARKER_FILE_NAME), content).map_err(|e| format!("写入归档标记失败: {}", e))?;
+    let readme = format!(
+        "本项目已于 {} 归档，共 {} 个文件。\r\n归档文件: {}\r\n如需继续使用，请在软件中恢复归档。\r\n",
+        marker.archived_at, marker.file_count, marker.archive_path
+    );
+    fs::write(project_dir.join(README_FILE_NAME), readme).map_err(|e| format!("写入说明文件失败: {}", e))
+}
+
+fn archive_blocking(
+    app: &AppHandle,
+    group_code: &str,
+    dest: &Path,
+    format: ArchiveFormat,
+    remove_local: bool,
+) -> Result<ArchiveResult, String> {
+    let location = sync_roots::locate(group_code).ok_or("本地未找到该项目")?;
+    let project_dir = PathBuf::from(&location.group.path);
+    let root = PathBuf::from(&location.root.path);
+    if is_archived(&project_dir) {
+        return Err("项目已归档".to_string());
+    }
+    if dest.starts_with(&project_dir) {
+        return Err("归档位置不能在项目文件夹内".to_string());
+    }
+    fs::create_dir_all(dest).map_err(|e| format!("创建目录失败: {}", e))?;
+
+    // 打包期间不让同步改动项目文件
+    let _run = sync_engine::begin_run()?;
+
+    let manifest = build_manifest(&project_dir, group_code, &location.group.group_name, format)?;
+    if manifest.files.is_empty() {
+        return Err("项目文件夹为空".to_string());
+    }
+    let total = manifest.files.len();
+
+    let file_name = format!(
+        "{}_{}.{}",
+        manifest.folder_name,
+        chrono::Local::now().format("%Y%m%d%H%M%S"),
+        format.extension()
+    );
+    let archive_path = dest.join(&file_name);
+    // 扩展名保留在末尾，便于校验时识别格式
+    let partial = dest.join(format!(".partial_{}", file_name));
+
+    let result = (|| {
+        let packing = |n| emit_progress(app, group_code, "packing", n, total);
+        match format {
+            ArchiveFormat::Zip => write_zip(&partial, &project_dir, &manifest, &packing)?,
+            ArchiveFormat::TarZst => write_tar_zst(&partial, &project_dir, &manifest, &packing)?,
+        }
+        verify(&partial, &manifest, &|n| emit_progress(app, group_code, "verifying", n, total))?;
+        fs::rename(&partial, &archive_path).map_err(|e| format!("保存归档失败: {}", e))
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+
+    let archive_size = fs::metadata(&archive_path).map(|m| m.len()).unwrap_or(0);
+    log::info!(
+        "[ProjectArchive] {} 已归档到 {:?}，{} 个文件，{} -> {} 字节",
+        group_code,
+        archive_path,
+        total,
+        manifest.total_size,
+        archive_size
+    );
+
+    if remove_local {
+        forget_group(&root, group_code)?;
+        let marker = ArchiveMarker {
+            archive_path: archive_path.to_string_lossy().to_string(),
+            archived_at: manifest.archived_at.clone(),
+            file_count: total,
+            total_size: manifest.total_size,
+        };
+        replace_with_placeholder(&project_dir, &marker)?;
+        log::info!("[ProjectArchive] {} 本地文件已删除，保留占位 {:?}", group_code, project_dir);
+    }
+
+    Ok(ArchiveResult {
+        archive_path: archive_path.to_string_lossy().to_string(),
+        file_count: total,
+        total_size: manifest.total_size,
+        archive_size,
+        removed_local: remove_local,
+    })
+}
+
+/// 本地已有文件时归档版本的副本路径
+fn restore_copy_path(target: &Path) -> PathBuf {
+    let mut path = sync_conflict::conflict_copy_path(target, RESTORE_COPY_LABEL);
+    let mut n = 2;
+    while path.exists() {
+        path = sync_conflict::conflict_copy_path(target, &format!("{}{}", RESTORE_COPY_LABEL, n));
+        n += 1;
+    }
+    path
+}
+
+fn restore_blocking(
+    app: &AppHandle,
+    group_code: Option<&str>,
+    archive_path: Option<&Path>,
+    dest_root: Option<&Path>,
+) -> Result<RestoreResult, String> {
+    let placeholder = group_code.and_then(sync_roots::locate).map(|l| PathBuf::from(l.group.path));
+    let archive_path = match archive_path {
+        Some(p) => p.to_path_buf(),
+        None => placeholder
+            .as_deref()
+            .and_then(read_marker)
+            .map(|m| PathBuf::from(m.archive_path))
+            .ok_or("未找到归档标记，请指定归档文件")?,
+    };
+    if !archive_path.is_file() {
+        return Err(format!("归档文件不存在: {}", archive_path.display()));
+    }
+
+    let manifest = read_manifest(&archive_path)?;
+    if group_code.is_some_and(|c| c != manifest.group_code) {
+        return Err("归档文件与项目编号不一致".to_string());
+    }
+
+    // 优先恢复到占位文件夹；没有占位时放到指定或最高优先级的根目录
+    let project_dir = match (placeholder, dest_root) {
+        (_, Some(root)) => root.join(&manifest.folder_name),
+        (Some(p), None) => p,
+        (None, None) => {
+            let root = sync_roots::enabled().into_iter().next().ok_or("未配置同步根目录")?;
+            Path::new(&root.path).join(&manifest.folder_name)
+        }
+    };
+
+    let _run = sync_engine::begin_run()?;
+
+    let expected: HashMap<&str, &ManifestFile> = manifest.files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let total = manifest.files.len();
+    let mut restored = 0;
+    let mut copies = Vec::new();
+
+    for_each_entry(&archive_path, |name, reader| {
+        if name == MANIFEST_NAME {
+            return Ok(true);
+        }
+        let (rel, file) = match entry_rel_path(&manifest, name).and_then(|rel| Some((rel, expected.get(rel)?))) {
+            Some(found) => found,
+            None => {
+                log::warn!("[ProjectArchive] 跳过清单外的条目: {}", name);
+                return Ok(true);
+            }
+        };
+
+        let mut target = project_dir.join(rel);
+        if target.exists() {
+            target = restore_copy_path(&target);
+            copies.push(target.to_string_lossy().to_string());
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+
+        let mut out = BufWriter::new(File::create(&target).map_err(|e| format!("创建文件失败 {}: {}", rel, e))?);
+        let (hash, _) = copy_hashed(reader, Some(&mut out))
+            .and_then(|r| out.flush().map(|_| r))
+            .map_err(|e| format!("解压文件失败 {}: {}", rel, e))?;
+        if hash != file.sha256 {
+            let _ = fs::remove_file(&target);
+            return Err(format!("归档文件已损坏: {}", rel));
+        }
+
+        restored += 1;
+        emit_progress(app, &manifest.group_code, "restoring", restored, total);
+        Ok(true)
+    })?;
+
+    let _ = fs::remove_file(project_dir.join(MARKER_FILE_NAME));
+    let _ = fs::remove_file(project_dir.join(README_FILE_NAME));
+
+    log::info!(
+        "[ProjectArchive] {} 已从 {:?} 恢复 {} 个文件，副本 {} 个",
+        manifest.group_code,
+        archive_path,
+        restored,
+        copies.len()
+    );
+    Ok(RestoreResult {
+        group_code: manifest.group_code,
+        path: project_dir.to_string_lossy().to_string(),
+        restored_files: restored,
+        copies,
+    })
+}
+
+/// 将项目打包为归档文件并校验
+///
+/// dest: 归档文件保存目录；remove_local 为 true 时校验通过后删除本地文件，只保留占位文件夹
+#[tauri::command]
+pub async fn archive_project(
+    app: AppHandle,
+    group_code: String,
+    dest: String,
+    format: Option<ArchiveFormat>,
+    remove_local: bool,
+) -> Result<ArchiveResult, String> {
+    tokio::task::spawn_blocking(move || {
+        archive_blocking(&app, &group_code, Path::new(&dest), format.unwrap_or_default(), remove_local)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 从归档文件恢复项目
+///
+/// 未指定 archive_path 时从占位文件夹的归档标记读取；本地已有的文件不覆盖，归档版本以副本保留
+#[tauri::command]
+pub async fn restore_archived_project(
+    app: AppHandle,
+    group_code: Option<String>,
+    archive_path: Option<String>,
+    dest_root: Option<String>,
+) -> Result<RestoreResult, String> {
+    tokio::task::spawn_blocking(move || {
+        restore_blocking(
+            &app,
+            group_code.as_deref(),
+            archive_path.as_deref().map(Path::new),
+            dest_root.as_deref().map(Path::new),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,226 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use crate::commands;
+use crate::scanner;
+use crate::sync_conflict;
+use crate::sync_engine;
+use crate::sync_trash::{self, TrashReason};
+
+/// 合并时与目标文件内容不同的文件保留为副本
+const MERGE_COPY_LABEL: &str = "合并副本";
+
+/// 整理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconcileAction {
+    /// 已是当前名称
+    Unchanged,
+    /// 本地不存在该项目
+    NotFound,
+    /// 旧名称文件夹已重命名
+    Renamed,
+    /// 多个文件夹已合并
+    Merged,
+}
+
+/// 项目文件夹整理报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconcileReport {
+    pub group_code: String,
+    pub action: ReconcileAction,
+    /// 整理后的项目文件夹
+    pub path: String,
+    /// 被重命名或合并的旧文件夹
+    pub previous: Vec<String>,
+    pub moved_files: u32,
+    /// 与目标内容相同、已移入回收站的文件
+    pub duplicates: u32,
+    /// 内容不同、以副本保留的文件
+    pub copies: Vec<String>,
+    /// 未能移动的文件，原文件夹会保留
+    pub errors: Vec<String>,
+}
+
+impl ReconcileReport {
+    fn new(group_code: &str, path: &Path) -> Self {
+        Self {
+            group_code: group_code.to_string(),
+            action: ReconcileAction::Unchanged,
+            path: path.to_string_lossy().to_string(),
+            previous: Vec::new(),
+            moved_files: 0,
+            duplicates: 0,
+            copies: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// 根目录下属于该项目编号的文件夹，按名称排序
+pub fn find_group_folders(root: &Path, group_code: &str) -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            scanner::parse_group_folder(&name, &e.path()).is_some_and(|g| g.group_code == group_code)
+        })
+        .map(|e| e.path())
+        .collect();
+    folders.sort();
+    folders
+}
+
+fn same_content(a: &Path, b: &Path) -> bool {
+    let size = |p: &Path| fs::metadata(p).map(|m| m.len()).ok();
+    size(a).is_some() && size(a) == size(b) && commands::sha256_file(a).ok() == commands::sha256_file(b).ok()
+}
+
+/// 目标已存在时的副本路径
+fn copy_path(target: &Path) -> PathBuf {
+    let mut path = sync_conflict::conflict_copy_path(target, MERGE_COPY_LABEL);
+    let mut n = 2;
+    while path.exists() {
+        path = sync_conflict::conflict_copy_path(target, &format!("{}{}", MERGE_COPY_LABEL, n));
+        n += 1;
+    }
+    path
+}
+
+/// 将 src 的内容合并到 dst，完成后删除空的 src
+///
+/// 目标不存在的直接移入；内容相同的移入回收站；内容不同的以副本保留，不覆盖任何文件。
+fn merge_dir(root: &Path, src: &Path, dst: &Path, report: &mut ReconcileReport) {
+    let entries = match fs::read_dir(src) {
+        Ok(e) => e,
+        Err(e) => {
+            report.errors.push(format!("{}: {}", src.display(), e));
+            return;
+        }
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let from = entry.path();
+        let to = dst.join(entry.file_name());
+
+        if from.is_dir() && to.is_dir() {
+            merge_dir(root, &from, &to, report);
+            continue;
+        }
+
+        let result = if !to.exists() {
+            fs::rename(&from, &to).map_err(|e| e.to_string()).map(|_| {
+                report.moved_files += 1;
+            })
+        } else if from.is_file() && to.is_file() && same_content(&from, &to) {
+            sync_trash::move_to_trash(root, &from, TrashReason::MergeDuplicate).map(|_| {
+                report.duplicates += 1;
+            })
+        } else {
+            let copy = copy_path(&to);
+            fs::rename(&from, &copy).map_err(|e| e.to_string()).map(|_| {
+                report.moved_files += 1;
+                report.copies.push(copy.to_string_lossy().to_string());
+            })
+        };
+        if let Err(e) = result {
+            report.errors.push(format!("{}: {}", from.display(), e));
+        }
+    }
+
+    // 还有未移走的内容时保留原文件夹
+    let _ = fs::remove_dir(src);
+}
+
+/// 按项目编号整理根目录下的项目文件夹：旧名称文件夹重命名为 folder_name，存在多个时合并
+///
+/// folder_name 为规范化后的 `Q编号_名称`。同步进行中时返回错误，由调用方稍后重试。
+pub fn reconcile(root: &Path, folder_name: &str) -> Result<ReconcileReport, String> {
+    let target = root.join(folder_name);
+    let group = scanner::parse_group_folder(folder_name, &target).ok_or("无效的项目文件夹名称")?;
+    let mut report = ReconcileReport::new(&group.group_code, &target);
+
+    let others: Vec<PathBuf> = find_group_folders(root, &group.group_code)
+        .into_iter()
+        .filter(|p| p != &target)
+        .collect();
+    if others.is_empty() {
+        if !target.exists() {
+            report.action = ReconcileAction::NotFound;
+        }
+        return Ok(report);
+    }
+
+    // 移动过程中不能让同步看到不完整的文件夹
+    let _run = sync_engine::begin_run()?;
+
+    let mut sources = others.into_iter();
+    if !target.exists() {
+        if let Some(first) = sources.next() {
+            fs::rename(&first, &target).map_err(|e| format!("重命名项目文件夹失败: {}", e))?;
+            report.previous.push(first.to_string_lossy().to_string());
+            report.action = ReconcileAction::Renamed;
+        }
+    }
+    for source in sources {
+        merge_dir(root, &source, &target, &mut report);
+        report.previous.push(source.to_string_lossy().to_string());
+        report.action = ReconcileAction::Merged;
+    }
+
+    log::info!(
+        "[ProjectReconcile] {} {:?}: {:?} -> {:?}，移动 {} 个，重复 {} 个，副本 {} 个，失败 {} 个",
+        report.group_code,
+        report.action,
+        report.previous,
+        report.path,
+        report.moved_files,
+        report.duplicates,
+        report.copies.len(),
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+/// 整理并在有变化时通知前端
+pub fn reconcile_and_notify(app: &AppHandle, root: &Path, folder_name: &str) -> Result<ReconcileReport, String> {
+    let report = reconcile(root, folder_name)?;
+    if matches!(report.action, ReconcileAction::Renamed | ReconcileAction::Merged) {
+        let _ = app.emit("project-folder-reconciled", report.clone());
+    }
+    Ok(report)
+}
+
+/// 按 CRM 中的当前名称批量整理项目文件夹
+///
+/// project_names: 项目文件夹名称（与 open_project_folder 的 project_name 相同格式）
+#[tauri::command]
+pub async fn reconcile_project_folders(
+    app: AppHandle,
+    work_dir: String,
+    project_names: Vec<String>,
+) -> Result<Vec<ReconcileReport>, String> {
+    tokio::task::spawn_blocking(move || reconcile_blocking(&app, Path::new(&work_dir), project_names))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn reconcile_blocking(app: &AppHandle, root: &Path, project_names: Vec<String>) -> Result<Vec<ReconcileReport>, String> {
+    if !root.is_dir() {
+        return Err("目录不存在".to_string());
+    }
+
+    let mut reports = Vec::new();
+    for name in project_names {
+        let folder_name = crate::file_sync::normalize_project_name(&name);
+        match reconcile_and_notify(app, root, &folder_name) {
+            Ok(report) => reports.push(report),
+            Err(e) => log::warn!("[ProjectReconcile] 整理 {} 失败: {}", folder_name, e),
+        }
+    }
+    Ok(reports)
+}
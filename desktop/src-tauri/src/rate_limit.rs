@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+
+/// 按平均速率限速：累计传输量超过允许值时等待
+pub struct RateLimiter {
+    bytes_per_sec: u64,
+    started: Instant,
+    transferred: u64,
+}
+
+impl RateLimiter {
+    /// 限速为空或 0 时不限速
+    pub fn from_kbps(kbps: Option<u64>) -> Option<Self> {
+        match kbps {
+            Some(k) if k > 0 => Some(Self {
+                bytes_per_sec: k * 1024,
+                started: Instant::now(),
+                transferred: 0,
+            }),
+            _ => None,
+        }
+    }
+
+    /// 记录已传输的字节数，超出速率时等待到允许的时间点
+    pub async fn consume(&mut self, bytes: u64) {
+        self.transferred += bytes;
+        let expected = Duration::from_secs_f64(self.transferred as f64 / self.bytes_per_sec as f64);
+        let elapsed = self.started.elapsed();
+        if expected > elapsed {
+            tokio::time::sleep(expected - elapsed).await;
+        }
+    }
+}
+
+/// 可选限速器的便捷调用
+pub async fn throttle(limiter: &mut Option<RateLimiter>, bytes: u64) {
+    if let Some(l) = limiter.as_mut() {
+        l.consume(bytes).await;
+    }
+}
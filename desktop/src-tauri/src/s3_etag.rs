@@ -0,0 +1,119 @@
+use md5::{Digest, Md5};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use crate::file_hash::{self, HashAlgorithm};
+
+/// 读取缓冲区大小
+const BUFFER_SIZE: usize = 1024 * 1024;
+/// 服务端未记录分片大小时依次尝试的常见分片大小（含本服务上传接口使用的 50MB 和 90MB）
+const COMMON_PART_SIZES: &[u64] = &[
+    5 * 1024 * 1024,
+    8 * 1024 * 1024,
+    10 * 1024 * 1024,
+    16 * 1024 * 1024,
+    32 * 1024 * 1024,
+    50 * 1024 * 1024,
+    64 * 1024 * 1024,
+    90 * 1024 * 1024,
+];
+/// 最多尝试的候选分片大小数量
+const MAX_CANDIDATES: usize = 3;
+
+/// 去掉引号并转为小写，返回 (MD5 十六进制, 分片数)；单段上传的 ETag 分片数为 None
+pub fn parse(etag: &str) -> Option<(String, Option<u32>)> {
+    let etag = etag.trim().trim_matches('"').to_ascii_lowercase();
+    let (digest, parts) = match etag.split_once('-') {
+        Some((digest, parts)) => (digest.to_string(), Some(parts.parse::<u32>().ok()?)),
+        None => (etag, None),
+    };
+    (digest.len() == 32 && digest.bytes().all(|b| b.is_ascii_hexdigit())).then_some((digest, parts))
+}
+
+/// 由各分片 ETag（分片 MD5）组合出分片上传对象的 ETag：`md5(各分片 MD5 拼接)-分片数`
+pub fn combine(part_etags: &[String]) -> Option<String> {
+    let mut hasher = Md5::new();
+    for etag in part_etags {
+        let (digest, _) = parse(etag)?;
+        hasher.update(hex::decode(digest).ok()?);
+    }
+    Some(format!("{}-{}", hex::encode(hasher.finalize()), part_etags.len()))
+}
+
+/// 按 part_size 分片后的分片数，空文件也算一个分片
+pub fn part_count(size: u64, part_size: u64) -> u32 {
+    size.div_ceil(part_size).max(1) as u32
+}
+
+fn compute_uncached(path: &Path, part_size: u64) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("无法打开文件: {}", e))?;
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    let mut part_digests = Vec::new();
+    let mut part = Md5::new();
+    let mut in_part = 0u64;
+    loop {
+        let want = (part_size - in_part).min(BUFFER_SIZE as u64) as usize;
+        let bytes_read = file.read(&mut buffer[..want]).map_err(|e| format!("读取文件失败: {}", e))?;
+        if bytes_read == 0 {
+            break;
+        }
+        part.update(&buffer[..bytes_read]);
+        in_part += bytes_read as u64;
+        if in_part == part_size {
+            part_digests.push(hex::encode(part.finalize_reset()));
+            in_part = 0;
+        }
+    }
+    if in_part > 0 || part_digests.is_empty() {
+        part_digests.push(hex::encode(part.finalize()));
+    }
+    combine(&part_digests).ok_or_else(|| "计算 ETag 失败".to_string())
+}
+
+/// 计算文件按 part_size 分片上传后的 S3 ETag，文件未变化时使用缓存
+pub fn compute(path: &Path, part_size: u64) -> Result<String, String> {
+    if part_size == 0 {
+        return Err("分片大小无效".to_string());
+    }
+    file_hash::cached_digest(path, &format!("s3-etag-{}", part_size), || compute_uncached(path, part_size))
+}
+
+/// 本地文件内容是否与服务端对象的 ETag 一致，无法判断时返回 None
+///
+/// 单段上传的 ETag 即文件 MD5；分片上传的 ETag 需要分片大小，未提供时按分片数从常见大小中推测。
+pub fn matches(path: &Path, etag: &str, part_size: Option<u64>) -> Option<bool> {
+    let (digest, parts) = parse(etag)?;
+    let parts = match parts {
+        Some(p) => p,
+        None => return file_hash::hash_file(path, HashAlgorithm::Md5).ok().map(|h| h == digest),
+    };
+
+    let size = std::fs::metadata(path).ok()?.len();
+    let expected = format!("{}-{}", digest, parts);
+    let candidates: Vec<u64> = match part_size.filter(|p| *p > 0) {
+        Some(p) => vec![p],
+        None => COMMON_PART_SIZES.iter().copied().filter(|p| part_count(size, *p) == parts).take(MAX_CANDIDATES).collect(),
+    };
+    if candidates.is_empty() {
+        return None;
+    }
+    if part_size.is_some() && part_count(size, candidates[0]) != parts {
+        return Some(false);
+    }
+    for candidate in &candidates {
+        if compute(path, *candidate).ok()? == expected {
+            return Some(true);
+        }
+    }
+    // 推测的分片大小可能与实际不同，不能断定内容不一致
+    part_size.map(|_| false)
+}
+
+/// 计算文件按 part_size（字节）分片上传后的 S3 ETag
+#[tauri::command]
+pub async fn compute_s3_etag(path: String, part_size: u64) -> Result<String, String> {
+    let path = PathBuf::from(path);
+    tokio::task::spawn_blocking(move || compute(&path, part_size))
+        .await
+        .map_err(|e| e.to_string())?
+}
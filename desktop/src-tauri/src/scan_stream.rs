@@ -0,0 +1,261 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use crate::commands::FolderFileInfo;
+use crate::scanner::{self, EntryKind, SkippedEntry, Traversal, TraversalPolicy, Visit};
+use crate::sync_ignore::SyncIgnore;
+
+/// 每批最多条目数
+const BATCH_SIZE: usize = 500;
+/// 条目不足一批时的最长发送间隔
+const BATCH_INTERVAL: Duration = Duration::from_millis(250);
+/// 汇总中保留的错误条数上限，超出部分只计数
+const MAX_ERRORS: usize = 1000;
+
+/// 扫描中遇到的单个错误，不中断扫描
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanEntryError {
+    pub path: String,
+    pub message: String,
+}
+
+/// 一批扫描结果及当前进度
+#[derive(Debug, Clone, Serialize)]
+struct ScanBatch {
+    scan_id: String,
+    entries: Vec<FolderFileInfo>,
+    files: usize,
+    dirs: usize,
+    total_size: u64,
+    errors: usize,
+    skipped: usize,
+}
+
+/// 扫描结束汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanSummary {
+    pub scan_id: String,
+    pub path: String,
+    pub files: usize,
+    pub dirs: usize,
+    pub total_size: u64,
+    pub errors: Vec<ScanEntryError>,
+    /// 错误总数，可能多于 errors 中保留的条数
+    pub error_count: usize,
+    /// 按遍历策略跳过的链接、循环和超出上限的条目
+    pub skipped: Vec<SkippedEntry>,
+    pub skipped_count: usize,
+    pub cancelled: bool,
+    pub elapsed_ms: u64,
+}
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_SCANS: Mutex<HashMap<String, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+struct ScanState<'a> {
+    app: &'a AppHandle,
+    traversal: &'a Traversal,
+    scan_id: &'a str,
+    root: &'a Path,
+    pending: Vec<FolderFileInfo>,
+    last_emit: Instant,
+    files: usize,
+    dirs: usize,
+    total_size: u64,
+    errors: Vec<ScanEntryError>,
+    error_count: usize,
+}
+
+impl ScanState<'_> {
+    fn error(&mut self, path: &Path, message: String) {
+        self.error_count += 1;
+        if self.errors.len() < MAX_ERRORS {
+            self.errors.push(ScanEntryError { path: path.to_string_lossy().to_string(), message });
+        }
+    }
+
+    fn push(&mut self, path: &Path, name: &str, size: u64) {
+        let relative = path.strip_prefix(self.root).unwrap_or(path);
+        self.pending.push(FolderFileInfo {
+            name: name.to_string(),
+            relative_path: relative.to_string_lossy().to_string().replace('\\', "/"),
+            absolute_path: path.to_string_lossy().to_string(),
+            size,
+        });
+        self.files += 1;
+        self.total_size += size;
+        if self.pending.len() >= BATCH_SIZE || self.last_emit.elapsed() >= BATCH_INTERVAL {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let _ = self.app.emit(
+            "scan-batch",
+            ScanBatch {
+                scan_id: self.scan_id.to_string(),
+                entries: std::mem::take(&mut self.pending),
+                files: self.files,
+                dirs: self.dirs,
+                total_size: self.total_size,
+                errors: self.error_count,
+                skipped: self.traversal.skipped_count(),
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+}
+
+fn scan_blocking(
+    app: &AppHandle,
+    scan_id: &str,
+    root: &Path,
+    policy: TraversalPolicy,
+    cancelled: &AtomicBool,
+) -> ScanSummary {
+    let started = Instant::now();
+    let traversal = Traversal::new(policy, root);
+    let mut state = ScanState {
+        app,
+        traversal: &traversal,
+        scan_id,
+        root,
+        pending: Vec::new(),
+        last_emit: Instant::now(),
+        files: 0,
+        dirs: 0,
+        total_size: 0,
+        errors: Vec::new(),
+        error_count: 0,
+    };
+
+    // 深度优先，按目录顺序发送
+    let mut stack: Vec<(PathBuf, SyncIgnore, usize)> = vec![(root.to_path_buf(), SyncIgnore::for_dir(root), 1)];
+    while let Some((dir, ignore, depth)) = stack.pop() {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        state.dirs += 1;
+
+        let entries = match scanner::list_dir(&dir) {
+            Ok(e) => e,
+            Err(e) => {
+                state.error(&dir, e);
+                continue;
+            }
+        };
+
+        let mut subdirs = Vec::new();
+        for (name, kind) in entries.iter() {
+            let path = dir.join(name);
+            if ignore.is_ignored(&path, *kind == EntryKind::Dir) {
+                continue;
+            }
+
+            match traversal.visit(&path, *kind, depth) {
+                Visit::Dir => subdirs.push(path),
+                Visit::File => match fs::metadata(&path) {
+                    Ok(m) => state.push(&path, name, m.len()),
+                    Err(e) => state.error(&path, format!("无法读取文件元数据: {}", e)),
+                },
+                Visit::Skip => {}
+            }
+        }
+
+        for subdir in subdirs.into_iter().rev() {
+            let child_ignore = ignore.with_child(&subdir);
+            stack.push((subdir, child_ignore, depth + 1));
+        }
+    }
+
+    let cancelled = cancelled.load(Ordering::Relaxed);
+    if !state.pending.is_empty() {
+        state.flush();
+    }
+
+    let summary = ScanSummary {
+        scan_id: scan_id.to_string(),
+        path: root.to_string_lossy().to_string(),
+        files: state.files,
+        dirs: state.dirs,
+        total_size: state.total_size,
+        errors: state.errors,
+        error_count: state.error_count,
+        skipped: traversal.skipped(),
+        skipped_count: traversal.skipped_count(),
+        cancelled,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    log::info!(
+        "[ScanStream] {} 扫描 {:?}{}：{} 个文件，{} 个目录，{} 个错误，跳过 {} 项，用时 {} ms",
+        scan_id,
+        root,
+        if cancelled { "（已取消）" } else { "" },
+        summary.files,
+        summary.dirs,
+        summary.error_count,
+        summary.skipped_count,
+        summary.elapsed_ms
+    );
+    summary
+}
+
+/// 流式扫描文件夹：通过 scan-batch 事件分批发送文件和进度，结束时发送 scan-finished 汇总
+///
+/// 单个条目读取失败只记录在汇总中，不中断扫描；policy 未指定时不跟随链接。返回值与 scan-finished 事件相同。
+#[tauri::command]
+pub async fn scan_folder_stream(
+    app: AppHandle,
+    scan_id: String,
+    path: String,
+    policy: Option<TraversalPolicy>,
+) -> Result<ScanSummary, String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("文件夹不存在".to_string());
+    }
+    if !root.is_dir() {
+        return Err("路径不是文件夹".to_string());
+    }
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut scans = ACTIVE_SCANS.lock().map_err(|e| e.to_string())?;
+        if scans.contains_key(&scan_id) {
+            return Err("扫描任务已存在".to_string());
+        }
+        scans.insert(scan_id.clone(), cancelled.clone());
+    }
+
+    let id = scan_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let summary = scan_blocking(&app, &id, &root, policy.unwrap_or_default(), &cancelled);
+        let _ = app.emit("scan-finished", summary.clone());
+        summary
+    })
+    .await
+    .map_err(|e| e.to_string());
+
+    if let Ok(mut scans) = ACTIVE_SCANS.lock() {
+        scans.remove(&scan_id);
+    }
+    result
+}
+
+/// 取消流式扫描，已发送的结果保留；扫描已结束时返回 false
+#[tauri::command]
+pub fn cancel_folder_scan(scan_id: String) -> bool {
+    match ACTIVE_SCANS.lock().ok().and_then(|scans| scans.get(&scan_id).cloned()) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
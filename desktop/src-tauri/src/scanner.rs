@@ -1,21 +1,352 @@
+use rayon::prelude::*;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 use crate::commands::{GroupFolder, LocalFile};
+use crate::folder_template;
+use crate::placeholder;
+use crate::selective_sync;
+use crate::sync_ignore::SyncIgnore;
+
+/// 跳过记录的条数上限，超出部分只计数
+const MAX_SKIPPED: usize = 1000;
+/// 目录修改时间的精度上限（FAT/SMB 为 2 秒），修改时间离当前不足此值的目录不缓存
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+/// 目录缓存的条目上限，超出时整体清空
+const MAX_CACHED_DIRS: usize = 50_000;
+
+/// 目录子项类型（不跟随符号链接）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Dir,
+    /// 符号链接或 Windows 目录联接
+    Link,
+    Other,
+}
+
+/// 目录遍历策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraversalPolicy {
+    /// 是否跟随符号链接和目录联接
+    #[serde(default)]
+    pub follow_links: bool,
+    /// 相对起始目录的最大层级
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// 最多遍历的条目数，0 表示不限
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_max_depth() -> usize {
+    64
+}
+
+fn default_max_entries() -> usize {
+    1_000_000
+}
+
+impl Default for TraversalPolicy {
+    fn default() -> Self {
+        Self {
+            follow_links: false,
+            max_depth: default_max_depth(),
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+/// 条目被跳过的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// 未跟随的链接，或链接目标不存在
+    Link,
+    /// 链接指向已遍历过的目录（如上级目录）
+    Loop,
+    MaxDepth,
+    MaxEntries,
+}
+
+/// 遍历时跳过的条目
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedEntry {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+/// 条目的遍历方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    File,
+    Dir,
+    Skip,
+}
+
+/// 目录的唯一标识：Unix 下为设备号和 inode，其他平台为解析链接后的规范路径
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+#[cfg(unix)]
+fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_id(path: &Path) -> Option<DirId> {
+    fs::canonicalize(path).ok()
+}
+
+/// 一次遍历的状态：按策略决定条目是否进入，记录跳过的条目；可在并行遍历中共用
+pub struct Traversal {
+    policy: TraversalPolicy,
+    visited: Mutex<HashSet<DirId>>,
+    entries: AtomicUsize,
+    truncated: AtomicBool,
+    skipped: Mutex<Vec<SkippedEntry>>,
+    skipped_count: AtomicUsize,
+}
+
+impl Traversal {
+    pub fn new(policy: TraversalPolicy, root: &Path) -> Self {
+        let traversal = Self {
+            policy,
+            visited: Mutex::new(HashSet::new()),
+            entries: AtomicUsize::new(0),
+            truncated: AtomicBool::new(false),
+            skipped: Mutex::new(Vec::new()),
+            skipped_count: AtomicUsize::new(0),
+        };
+        if traversal.policy.follow_links {
+            traversal.first_visit(root);
+        }
+        traversal
+    }
+
+    fn skip(&self, path: &Path, reason: SkipReason) -> Visit {
+        self.skipped_count.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut skipped) = self.skipped.lock() {
+            if skipped.len() < MAX_SKIPPED {
+                skipped.push(SkippedEntry { path: path.to_string_lossy().to_string(), reason });
+            }
+        }
+        Visit::Skip
+    }
+
+    /// 首次进入该目录时返回 true；无法识别的目录视为首次进入
+    fn first_visit(&self, dir: &Path) -> bool {
+        match (dir_id(dir), self.visited.lock()) {
+            (Some(id), Ok(mut visited)) => visited.insert(id),
+            _ => true,
+        }
+    }
+
+    /// 决定 path 处的条目如何遍历，depth 为条目相对起始目录的层级（直接子项为 1）
+    pub fn visit(&self, path: &Path, kind: EntryKind, depth: usize) -> Visit {
+        if kind == EntryKind::Other {
+            return Visit::Skip;
+        }
+        let count = self.entries.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.policy.max_entries > 0 && count > self.policy.max_entries {
+            // 只记录第一次超限的位置
+            return if self.truncated.swap(true, Ordering::Relaxed) {
+                Visit::Skip
+            } else {
+                self.skip(path, SkipReason::MaxEntries)
+            };
+        }
+
+        match kind {
+            EntryKind::File => return Visit::File,
+            EntryKind::Dir => {}
+            _ if !self.policy.follow_links => return self.skip(path, SkipReason::Link),
+            _ => match fs::metadata(path) {
+                Ok(m) if m.is_dir() => {}
+                Ok(m) if m.is_file() => return Visit::File,
+                Ok(_) => return Visit::Skip,
+                Err(_) => return self.skip(path, SkipReason::Link),
+            },
+        }
+
+        if depth > self.policy.max_depth {
+            self.truncated.store(true, Ordering::Relaxed);
+            return self.skip(path, SkipReason::MaxDepth);
+        }
+        if self.policy.follow_links && !self.first_visit(path) {
+            return self.skip(path, SkipReason::Loop);
+        }
+        Visit::Dir
+    }
+
+    /// 是否因层级或条目数上限漏掉了部分内容
+    pub fn truncated(&self) -> bool {
+        self.truncated.load(Ordering::Relaxed)
+    }
+
+    /// 跳过的条目总数，可能多于 skipped() 中保留的条数
+    pub fn skipped_count(&self) -> usize {
+        self.skipped_count.load(Ordering::Relaxed)
+    }
+
+    pub fn skipped(&self) -> Vec<SkippedEntry> {
+        self.skipped.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+/// 目录的直接子项，按目录修改时间缓存
+struct CachedDir {
+    modified: SystemTime,
+    entries: Arc<Vec<(String, EntryKind)>>,
+}
+
+lazy_static::lazy_static! {
+    static ref GROUP_FOLDER_RE: Regex = Regex::new(r"^(Q\d{10,})(?:_(.+))?$").unwrap();
+    static ref DIR_CACHE: RwLock<HashMap<PathBuf, CachedDir>> = RwLock::new(HashMap::new());
+}
+
+/// 一次扫描的统计
+#[derive(Default)]
+struct ScanStats {
+    dirs: AtomicUsize,
+    cached: AtomicUsize,
+}
+
+impl ScanStats {
+    fn summary(&self) -> (usize, usize) {
+        (self.dirs.load(Ordering::Relaxed), self.cached.load(Ordering::Relaxed))
+    }
+}
+
+fn dir_modified(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
+
+fn read_entries(dir: &Path) -> Result<Vec<(String, EntryKind)>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("无法读取目录: {}", e))?;
+    let mut listed = Vec::new();
+    for entry in entries.flatten() {
+        let name = match entry.file_name().to_str() {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let kind = match entry.file_type() {
+            Ok(t) if t.is_symlink() => EntryKind::Link,
+            Ok(t) if t.is_dir() => EntryKind::Dir,
+            Ok(t) if t.is_file() => EntryKind::File,
+            _ => EntryKind::Other,
+        };
+        listed.push((name, kind));
+    }
+    Ok(listed)
+}
+
+/// 列出目录的直接子项；目录修改时间未变时使用上次的结果
+///
+/// 目录修改时间只在子项增加、删除或改名时变化，文件内容修改不会影响，文件大小和修改时间仍需重新读取。
+pub fn list_dir(dir: &Path) -> Result<Arc<Vec<(String, EntryKind)>>, String> {
+    list_dir_with(dir, &ScanStats::default())
+}
+
+fn list_dir_with(dir: &Path, stats: &ScanStats) -> Result<Arc<Vec<(String, EntryKind)>>, String> {
+    stats.dirs.fetch_add(1, Ordering::Relaxed);
+    let modified = match dir_modified(dir) {
+        Some(m) => m,
+        None => {
+            if let Ok(mut cache) = DIR_CACHE.write() {
+                cache.remove(dir);
+            }
+            return read_entries(dir).map(Arc::new);
+        }
+    };
+
+    let cached = DIR_CACHE
+        .read()
+        .ok()
+        .and_then(|cache| cache.get(dir).filter(|c| c.modified == modified).map(|c| c.entries.clone()));
+    if let Some(entries) = cached {
+        stats.cached.fetch_add(1, Ordering::Relaxed);
+        return Ok(entries);
+    }
+
+    let entries = match read_entries(dir) {
+        Ok(e) => Arc::new(e),
+        Err(e) => {
+            if let Ok(mut cache) = DIR_CACHE.write() {
+                cache.remove(dir);
+            }
+            return Err(e);
+        }
+    };
+    if let Ok(mut cache) = DIR_CACHE.write() {
+        // 同一时间精度内的后续改动不会改变修改时间，此时缓存可能漏掉新子项
+        let settled = SystemTime::now()
+            .duration_since(modified)
+            .is_ok_and(|age| age >= MTIME_GRANULARITY);
+        if !settled {
+            cache.remove(dir);
+        } else {
+            if cache.len() >= MAX_CACHED_DIRS && !cache.contains_key(dir) {
+                cache.clear();
+            }
+            cache.insert(dir.to_path_buf(), CachedDir { modified, entries: entries.clone() });
+        }
+    }
+    Ok(entries)
+}
+
+/// 从文件夹名解析群编号（不访问文件系统）
+pub fn group_code_of(folder_name: &str) -> Option<String> {
+    GROUP_FOLDER_RE
+        .captures(folder_name)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+}
 
 pub fn parse_group_folder(folder_name: &str, path: &Path) -> Option<GroupFolder> {
-    let re = Regex::new(r"^(Q\d{10,})(?:_(.+))?$").ok()?;
-    
-    let captures = re.captures(folder_name)?;
+    let captures = GROUP_FOLDER_RE.captures(folder_name)?;
     let group_code = captures.get(1)?.as_str().to_string();
     let group_name = captures
         .get(2)
         .map(|m| m.as_str().to_string())
         .unwrap_or_else(|| "".to_string());
     
-    let has_works = path.join("作品文件").exists();
-    let has_models = path.join("模型文件").exists();
-    let has_customer = path.join("客户文件").exists();
+    let templates = folder_template::current();
+    // 项目文件夹未变化时不再逐个检查模板文件夹
+    let entries = list_dir(path).unwrap_or_default();
+    let exists = |name: &str| {
+        entries.iter().any(|(n, kind)| {
+            n == name && (*kind == EntryKind::Dir || (*kind == EntryKind::Link && path.join(name).is_dir()))
+        })
+    };
+    let has_folder = |asset_type: &str| {
+        templates
+            .find(asset_type)
+            .map(|f| exists(&f.name))
+            .unwrap_or(false)
+    };
+    
+    let has_works = has_folder("works");
+    let has_models = has_folder("models");
+    let has_customer = has_folder("customer");
+    
+    let folders = templates
+        .folders
+        .iter()
+        .filter(|f| exists(&f.name))
+        .map(|f| f.name.clone())
+        .collect();
+    
+    let sync_mode = selective_sync::current().mode_for(&group_code);
     
     Some(GroupFolder {
         group_code,
@@ -24,70 +355,155 @@ pub fn parse_group_folder(folder_name: &str, path: &Path) -> Option<GroupFolder>
         has_works,
         has_models,
         has_customer,
+        folders,
+        sync_mode,
     })
 }
 
-pub fn collect_files(base_path: &Path, current_path: &Path) -> Result<Vec<LocalFile>, String> {
-    let mut files = Vec::new();
+/// 扫描根目录下的群文件夹（Q 开头），按群编号排序
+pub fn scan_groups(root: &Path) -> Result<Vec<GroupFolder>, String> {
+    let started = Instant::now();
+    let stats = ScanStats::default();
+    let ignore = SyncIgnore::for_dir(root);
+    
+    let entries = list_dir_with(root, &stats)?;
+    
+    let mut groups: Vec<GroupFolder> = entries
+        .par_iter()
+        .filter(|(name, kind)| {
+            matches!(kind, EntryKind::Dir | EntryKind::Link) && GROUP_FOLDER_RE.is_match(name)
+        })
+        .filter_map(|(folder_name, _)| {
+            let entry_path = root.join(folder_name);
+            if !entry_path.is_dir() || ignore.is_ignored(&entry_path, true) {
+                return None;
+            }
+            parse_group_folder(folder_name, &entry_path)
+        })
+        .collect();
     
+    groups.sort_by(|a, b| a.group_code.cmp(&b.group_code));
+    
+    log::info!(
+        "[Scanner] 扫描 {:?}：{} 个项目，用时 {} ms",
+        root,
+        groups.len(),
+        started.elapsed().as_millis()
+    );
+    Ok(groups)
+}
+
+/// 递归收集 current_path 下的文件和文件夹，子文件夹并行扫描
+///
+/// 不跟随链接；超出层级或条目数上限时返回错误，避免漏掉的文件被当作已删除。
+pub fn collect_files(base_path: &Path, current_path: &Path, ignore: &SyncIgnore) -> Result<Vec<LocalFile>, String> {
+    let started = Instant::now();
+    let stats = ScanStats::default();
+    let traversal = Traversal::new(TraversalPolicy::default(), current_path);
+    let files = walk_files(base_path, current_path, ignore, &traversal, 1, &stats)?;
+    if traversal.truncated() {
+        return Err(format!("{:?} 层级过深或文件过多，已停止扫描", current_path));
+    }
+    let (dirs, cached) = stats.summary();
+    log::debug!(
+        "[Scanner] 扫描 {:?}：{} 项，{} 个目录（{} 个未变化），跳过 {} 个链接，用时 {} ms",
+        current_path,
+        files.len(),
+        dirs,
+        cached,
+        traversal.skipped_count(),
+        started.elapsed().as_millis()
+    );
+    Ok(files)
+}
+
+fn walk_files(
+    base_path: &Path,
+    current_path: &Path,
+    ignore: &SyncIgnore,
+    traversal: &Traversal,
+    depth: usize,
+    stats: &ScanStats,
+) -> Result<Vec<LocalFile>, String> {
     if !current_path.exists() || !current_path.is_dir() {
-        return Ok(files);
+        return Ok(Vec::new());
     }
     
-    let entries = fs::read_dir(current_path)
-        .map_err(|e| format!("无法读取目录: {}", e))?;
+    let entries = list_dir_with(current_path, stats)?;
     
-    for entry in entries {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        
-        let entry_path = entry.path();
-        let metadata = match entry.metadata() {
-            Ok(m) => m,
-            Err(_) => continue,
-        };
-        
-        let filename = match entry.file_name().to_str() {
-            Some(name) => name.to_string(),
-            None => continue,
-        };
-        
-        if filename.starts_with('.') {
-            continue;
-        }
-        
-        let rel_path = entry_path
-            .strip_prefix(base_path)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| filename.clone());
-        
-        let modified_at = metadata.modified()
-            .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
-            .unwrap_or(0);
-        
-        if metadata.is_file() {
-            files.push(LocalFile {
-                rel_path,
-                filename,
-                size: metadata.len(),
-                modified_at,
-                is_dir: false,
-            });
-        } else if metadata.is_dir() {
-            files.push(LocalFile {
-                rel_path: rel_path.clone(),
-                filename: filename.clone(),
-                size: 0,
-                modified_at,
-                is_dir: true,
-            });
+    let groups: Vec<Vec<LocalFile>> = entries
+        .par_iter()
+        .map(|(filename, kind)| -> Result<Vec<LocalFile>, String> {
+            let mut files = Vec::new();
+            if filename.starts_with('.') {
+                return Ok(files);
+            }
             
-            let sub_files = collect_files(base_path, &entry_path)?;
-            files.extend(sub_files);
-        }
-    }
+            let entry_path = current_path.join(filename);
+            if ignore.is_ignored(&entry_path, *kind == EntryKind::Dir) {
+                return Ok(files);
+            }
+            let visit = traversal.visit(&entry_path, *kind, depth);
+            if visit == Visit::Skip {
+                return Ok(files);
+            }
+            
+            let metadata = match fs::metadata(&entry_path) {
+                Ok(m) => m,
+                Err(_) => return Ok(files),
+            };
+            
+            let rel_path = entry_path
+                .strip_prefix(base_path)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| filename.clone());
+            
+            let modified_at = metadata.modified()
+                .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+                .unwrap_or(0);
+            
+            if visit == Visit::File && metadata.is_file() {
+                // 云端占位文件按真实文件列出，真实文件已存在时忽略残留的占位文件
+                if let Some(real) = placeholder::real_path(&entry_path) {
+                    if let Some(ph) = placeholder::read(&entry_path) {
+                        if !real.exists() {
+                            files.push(LocalFile {
+                                rel_path: rel_path[..rel_path.len() - placeholder::PLACEHOLDER_SUFFIX.len()].to_string(),
+                                filename: real.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                                size: ph.remote.size,
+                                modified_at,
+                                is_dir: false,
+                                placeholder: Some(ph.remote.storage_key),
+                            });
+                        }
+                        return Ok(files);
+                    }
+                }
+                
+                files.push(LocalFile {
+                    rel_path,
+                    filename: filename.clone(),
+                    size: metadata.len(),
+                    modified_at,
+                    is_dir: false,
+                    placeholder: None,
+                });
+            } else if visit == Visit::Dir && metadata.is_dir() {
+                files.push(LocalFile {
+                    rel_path,
+                    filename: filename.clone(),
+                    size: 0,
+                    modified_at,
+                    is_dir: true,
+                    placeholder: None,
+                });
+                
+                let child_ignore = ignore.with_child(&entry_path);
+                files.extend(walk_files(base_path, &entry_path, &child_ignore, traversal, depth + 1, stats)?);
+            }
+            Ok(files)
+        })
+        .collect::<Result<_, String>>()?;
     
-    Ok(files)
+    Ok(groups.into_iter().flatten().collect())
 }
@@ -0,0 +1,321 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use crate::folder_template;
+use crate::placeholder::{self, Placeholder};
+use crate::scanner;
+use crate::sync_client::RemoteResource;
+use crate::sync_engine;
+use crate::sync_index::SyncIndex;
+use crate::sync_roots;
+
+/// 选择性同步配置文件名（位于应用配置目录）
+pub const SETTINGS_FILE_NAME: &str = "selective_sync.json";
+const LISTING_DIR_NAME: &str = "listing";
+
+/// 项目同步方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// 完整同步
+    #[default]
+    Full,
+    /// 只同步文件列表，不自动下载文件内容
+    MetadataOnly,
+    /// 按需下载：服务端文件在本地显示为占位文件，打开时再下载
+    OnDemand,
+    /// 不同步
+    Excluded,
+}
+
+/// 单个项目的同步设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectSyncSetting {
+    #[serde(default)]
+    pub mode: SyncMode,
+    /// 不同步的文件夹（相对项目目录，如 `作品文件/渲染`）
+    #[serde(default)]
+    pub excluded_folders: Vec<String>,
+}
+
+/// 选择性同步设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SelectiveSyncSettings {
+    /// 未单独设置的项目使用的同步方式（设为 excluded 即只同步列出的项目）
+    #[serde(default)]
+    pub default_mode: SyncMode,
+    /// 所有项目都不同步的文件夹
+    #[serde(default)]
+    pub excluded_folders: Vec<String>,
+    /// 按群编号单独设置
+    #[serde(default)]
+    pub projects: BTreeMap<String, ProjectSyncSetting>,
+}
+
+fn under_folder(rel_path: &str, folder: &str) -> bool {
+    let folder = folder.trim_matches('/');
+    !folder.is_empty()
+        && (rel_path == folder || rel_path.strip_prefix(folder).is_some_and(|rest| rest.starts_with('/')))
+}
+
+impl SelectiveSyncSettings {
+    pub fn mode_for(&self, group_code: &str) -> SyncMode {
+        self.projects
+            .get(group_code)
+            .map(|p| p.mode)
+            .unwrap_or(self.default_mode)
+    }
+
+    /// rel_path 为相对项目目录的路径（以 / 分隔）
+    pub fn is_excluded(&self, group_code: &str, rel_path: &str) -> bool {
+        let project_folders = self
+            .projects
+            .get(group_code)
+            .map(|p| p.excluded_folders.as_slice())
+            .unwrap_or_default();
+        self.excluded_folders
+            .iter()
+            .chain(project_folders)
+            .any(|folder| under_folder(rel_path, folder))
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SETTINGS: RwLock<SelectiveSyncSettings> = RwLock::new(SelectiveSyncSettings::default());
+    static ref SETTINGS_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// 启动时加载选择性同步设置
+pub fn init(config_dir: PathBuf) {
+    let path = config_dir.join(SETTINGS_FILE_NAME);
+    if let Ok(mut p) = SETTINGS_PATH.write() {
+        *p = Some(path.clone());
+    }
+    if !path.exists() {
+        return;
+    }
+
+    let loaded = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str::<SelectiveSyncSettings>(&c).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(settings) => {
+            if let Ok(mut s) = SETTINGS.write() {
+                *s = settings;
+            }
+        }
+        Err(e) => log::warn!("[SelectiveSync] 加载设置失败，同步全部项目: {}", e),
+    }
+}
+
+/// 当前设置
+pub fn current() -> SelectiveSyncSettings {
+    SETTINGS.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+fn save(settings: SelectiveSyncSettings) -> Result<(), String> {
+    let path = SETTINGS_PATH
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("设置路径未初始化")?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("保存设置失败: {}", e))?;
+
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+/// 检查下载目标是否位于不同步的项目或文件夹中
+pub fn check_download_path(path: &Path) -> Result<(), String> {
+    let root = match sync_roots::root_of(path) {
+        Some(root) => PathBuf::from(root.path),
+        None => return Ok(()),
+    };
+    let rel = match path.strip_prefix(&root) {
+        Ok(rel) => rel,
+        Err(_) => return Ok(()),
+    };
+
+    let mut parts = rel.components().filter_map(|c| match c {
+        Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+        _ => None,
+    });
+    let project = match parts.next() {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+    let group = match scanner::parse_group_folder(&project, &root.join(&project)) {
+        Some(g) => g,
+        None => return Ok(()),
+    };
+
+    let settings = current();
+    if settings.mode_for(&group.group_code) == SyncMode::Excluded {
+        return Err(format!("项目 {} 已设置为不同步", group.group_code));
+    }
+    let rel_path = parts.collect::<Vec<_>>().join("/");
+    if settings.is_excluded(&group.group_code, &rel_path) {
+        return Err(format!("{} 位于不同步的文件夹中", rel_path));
+    }
+    Ok(())
+}
+
+fn listing_path(root: &Path, group_code: &str) -> PathBuf {
+    SyncIndex::meta_dir(root)
+        .join(LISTING_DIR_NAME)
+        .join(format!("{}.json", group_code))
+}
+
+/// 保存仅同步列表项目的服务端文件列表
+pub fn save_listing(root: &Path, group_code: &str, asset_type: &str, resources: &[RemoteResource]) -> Result<(), String> {
+    let path = listing_path(root, group_code);
+    let mut listing = load_listing(root, group_code)?;
+    listing.insert(asset_type.to_string(), resources.to_vec());
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string(&listing).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("保存文件列表失败: {}", e))
+}
+
+fn load_listing(root: &Path, group_code: &str) -> Result<BTreeMap<String, Vec<RemoteResource>>, String> {
+    let path = listing_path(root, group_code);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取文件列表失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析文件列表失败: {}", e))
+}
+
+/// 移除本地文件的结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EvictResult {
+    pub removed: u32,
+    pub freed_bytes: u64,
+    /// 有未同步修改而保留的文件
+    pub kept: Vec<String>,
+}
+
+/// 获取选择性同步设置
+#[tauri::command]
+pub fn get_selective_sync() -> SelectiveSyncSettings {
+    current()
+}
+
+/// 保存选择性同步设置
+#[tauri::command]
+pub fn save_selective_sync(settings: SelectiveSyncSettings) -> Result<(), String> {
+    save(settings)
+}
+
+/// 设置单个项目的同步方式
+#[tauri::command]
+pub fn set_project_sync_mode(group_code: String, mode: SyncMode) -> Result<(), String> {
+    let mut settings = current();
+    settings.projects.entry(group_code).or_default().mode = mode;
+    save(settings)
+}
+
+/// 获取项目的服务端文件列表（仅同步列表的项目）
+#[tauri::command]
+pub fn get_project_listing(group_code: String) -> Result<BTreeMap<String, Vec<RemoteResource>>, String> {
+    let location = sync_roots::locate(&group_code).ok_or("本地不存在该项目")?;
+    load_listing(Path::new(&location.root.path), &group_code)
+}
+
+/// 移除项目的本地文件并改为只同步列表；有未同步修改的文件会保留
+#[tauri::command]
+pub fn evict_project(group_code: String) -> Result<EvictResult, String> {
+    let _run = sync_engine::begin_run()?;
+
+    let location = sync_roots::locate(&group_code).ok_or("本地不存在该项目")?;
+    let root = PathBuf::from(&location.root.path);
+    let group = location.group;
+
+    let mut settings = current();
+    let project = settings.projects.entry(group_code.clone()).or_default();
+    if project.mode == SyncMode::Full {
+        project.mode = SyncMode::MetadataOnly;
+    }
+    // 按需下载的项目移除后保留占位文件
+    let leave_placeholders = project.mode == SyncMode::OnDemand;
+    save(settings)?;
+
+    let mut index = SyncIndex::load(&root)?;
+    let mut result = EvictResult::default();
+
+    for template in &folder_template::current().folders {
+        let asset_type = match &template.asset_type {
+            Some(t) => t,
+            None => continue,
+        };
+        let asset_dir = Path::new(&group.path).join(&template.name);
+
+        for (rel_path, entry) in index.entries_under(&group_code, asset_type) {
+            let key = SyncIndex::key(&group_code, asset_type, &rel_path);
+            let path = asset_dir.join(&rel_path);
+            let metadata = match fs::metadata(&path) {
+                Ok(m) => m,
+                Err(_) => {
+                    index.remove(&key);
+                    continue;
+                }
+            };
+            let modified_at = metadata
+                .modified()
+                .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+                .unwrap_or(0);
+
+            // 只移除与服务端一致的文件
+            if entry.storage_key.is_none() || metadata.len() != entry.size || modified_at != entry.modified_at {
+                result.kept.push(format!("{}/{}", template.name, rel_path));
+                continue;
+            }
+
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("[SelectiveSync] 删除 {:?} 失败: {}", path, e);
+                result.kept.push(format!("{}/{}", template.name, rel_path));
+                continue;
+            }
+            if leave_placeholders {
+                if let Some(storage_key) = entry.storage_key.clone() {
+                    let remote = RemoteResource {
+                        rel_path: rel_path.clone(),
+                        filename: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                        size: entry.remote_size,
+                        modified_at: entry.remote_modified_at.clone(),
+                        storage_key,
+                        is_dir: false,
+                        hash: entry.hash.clone(),
+                        etag: None,
+                        part_size: None,
+                    };
+                    let ph = Placeholder::new(&group_code, asset_type, &remote);
+                    if let Err(e) = placeholder::write(&path, &ph) {
+                        log::warn!("[SelectiveSync] 创建占位文件 {:?} 失败: {}", path, e);
+                    }
+                }
+            }
+            index.remove(&key);
+            result.removed += 1;
+            result.freed_bytes += metadata.len();
+        }
+    }
+
+    index.save()?;
+    log::info!(
+        "[SelectiveSync] 已移除项目 {} 的 {} 个本地文件，释放 {} 字节",
+        group_code,
+        result.removed,
+        result.freed_bytes
+    );
+    Ok(result)
+}
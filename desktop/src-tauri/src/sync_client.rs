@@ -0,0 +1,457 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use crate::chunker::ChunkManifest;
+use crate::delta_sync::{ChunkTransport, UploadTarget};
+use crate::rate_limit::{self, RateLimiter};
+use crate::sync_roots::SyncRoot;
+
+/// 单页拉取的资源数（服务端上限 500）
+const PAGE_SIZE: usize = 500;
+/// 块同步接口
+const DELTA_ENDPOINT: &str = "desktop_delta_sync.php";
+
+lazy_static::lazy_static! {
+    /// 各服务器是否支持块同步，每次启动只探测一次
+    static ref DELTA_SUPPORT: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+}
+
+/// 服务端资源（desktop_group_resources.php 返回项）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteResource {
+    pub rel_path: String,
+    pub filename: String,
+    #[serde(default, alias = "filesize", alias = "file_size")]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_at: Option<String>,
+    pub storage_key: String,
+    #[serde(default)]
+    pub is_dir: bool,
+    /// 内容 SHA-256（服务端提供时）
+    #[serde(default, alias = "sha256")]
+    pub hash: Option<String>,
+    /// 对象存储的 ETag，分片上传的对象为 `md5(各分片 MD5)-分片数`
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// 上传时使用的分片大小（字节）
+    #[serde(default)]
+    pub part_size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    success: bool,
+    data: Option<T>,
+    #[serde(default)]
+    error: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourcePage {
+    #[serde(default)]
+    items: Vec<RemoteResource>,
+    #[serde(default)]
+    total: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct InitUploadResponse {
+    upload_id: String,
+    part_size: u64,
+    total_parts: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadUrlResponse {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MoveResponse {
+    new_path: String,
+}
+
+/// 错误信息可能是字符串或 {code, message}
+fn error_message(error: Option<Value>) -> String {
+    match error {
+        Some(Value::String(s)) => s,
+        Some(Value::Object(o)) => o
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("未知错误")
+            .to_string(),
+        _ => "未知错误".to_string(),
+    }
+}
+
+/// 桌面端同步接口客户端
+#[derive(Clone)]
+pub struct SyncClient {
+    base_url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+    upload_limit_kbps: Option<u64>,
+    download_limit_kbps: Option<u64>,
+}
+
+impl SyncClient {
+    pub fn new(server_url: &str, token: Option<String>) -> Self {
+        Self {
+            base_url: format!("{}/api/", server_url.trim_end_matches('/')),
+            token,
+            http: reqwest::Client::new(),
+            upload_limit_kbps: None,
+            download_limit_kbps: None,
+        }
+    }
+
+    /// 使用根目录的限速设置
+    pub fn with_limits(mut self, root: &SyncRoot) -> Self {
+        self.upload_limit_kbps = root.upload_limit_kbps;
+        self.download_limit_kbps = root.download_limit_kbps;
+        self
+    }
+
+    /// 上传限速（KB/s）
+    pub fn upload_limit_kbps(&self) -> Option<u64> {
+        self.upload_limit_kbps
+    }
+
+    /// 下载限速（KB/s）
+    pub fn download_limit_kbps(&self) -> Option<u64> {
+        self.download_limit_kbps
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(t) => request.header("Authorization", format!("Bearer {}", t)),
+            None => request,
+        }
+    }
+
+    async fn parse<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, String> {
+        let status = response.status();
+        let body = response.text().await.map_err(|e| format!("读取响应失败: {}", e))?;
+        let parsed: ApiResponse<T> = serde_json::from_str(&body)
+            .map_err(|_| format!("非 JSON 响应 HTTP {}: {}", status, body.chars().take(200).collect::<String>()))?;
+
+        if !parsed.success {
+            return Err(error_message(parsed.error));
+        }
+        parsed.data.ok_or_else(|| "响应缺少 data".to_string())
+    }
+
+    /// 可选接口：服务端返回 404/501 或非 JSON 响应时视为不支持，返回 None
+    async fn send_optional<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<Option<T>, String> {
+        let response = self
+            .authorize(request)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::NOT_IMPLEMENTED {
+            return Ok(None);
+        }
+        let body = response.text().await.map_err(|e| format!("读取响应失败: {}", e))?;
+        let parsed: ApiResponse<T> = match serde_json::from_str(&body) {
+            Ok(p) => p,
+            Err(_) => return Ok(None),
+        };
+        if !parsed.success {
+            return Err(error_message(parsed.error));
+        }
+        Ok(parsed.data)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, String)]) -> Result<T, String> {
+        let request = self.http.get(format!("{}{}", self.base_url, endpoint)).query(query);
+        let response = self
+            .authorize(request)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+        Self::parse(response).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, endpoint: &str, body: Value) -> Result<T, String> {
+        let request = self
+            .http
+            .post(format!("{}{}", self.base_url, endpoint))
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+        let response = self
+            .authorize(request)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+        Self::parse(response).await
+    }
+
+    /// 拉取群资源列表（自动翻页）
+    pub async fn list_resources(&self, group_code: &str, asset_type: &str) -> Result<Vec<RemoteResource>, String> {
+        let mut resources = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let data: ResourcePage = self
+                .get(
+                    "desktop_group_resources.php",
+                    &[
+                        ("group_code", group_code.to_string()),
+                        ("asset_type", asset_type.to_string()),
+                        ("per_page", PAGE_SIZE.to_string()),
+                        ("page", page.to_string()),
+                    ],
+                )
+                .await?;
+
+            let count = data.items.len();
+            resources.extend(data.items.into_iter().filter(|r| !r.is_dir));
+
+            if count < PAGE_SIZE || page * PAGE_SIZE >= data.total {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(resources)
+    }
+
+    /// 获取资源下载地址
+    pub async fn download_url(&self, storage_key: &str) -> Result<String, String> {
+        let data: DownloadUrlResponse = self
+            .get(
+                "desktop_download.php",
+                &[("action", "get_url".to_string()), ("storage_key", storage_key.to_string())],
+            )
+            .await?;
+        Ok(data.url)
+    }
+
+    /// 删除服务端的群资源
+    pub async fn delete_resource(&self, group_code: &str, storage_key: &str) -> Result<(), String> {
+        let _: Value = self
+            .post(
+                "desktop_file_manage.php",
+                json!({ "action": "delete_group_resource", "group_code": group_code, "storage_key": storage_key }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 在服务端移动群资源（可跨目录），成功后才删除原文件，返回新的 storage_key
+    pub async fn move_resource(
+        &self,
+        group_code: &str,
+        storage_key: &str,
+        new_storage_key: &str,
+    ) -> Result<String, String> {
+        let data: MoveResponse = self
+            .post(
+                "desktop_file_manage.php",
+                json!({
+                    "action": "move_group_resource",
+                    "group_code": group_code,
+                    "storage_key": storage_key,
+                    "new_storage_key": new_storage_key,
+                }),
+            )
+            .await?;
+        Ok(data.new_path)
+    }
+
+    /// 服务端是否提供块同步接口；不支持时调用方直接整体传输，不再切分和计算块哈希
+    pub async fn supports_delta(&self) -> bool {
+        let cached = DELTA_SUPPORT.lock().ok().and_then(|m| m.get(&self.base_url).copied());
+        if let Some(supported) = cached {
+            return supported;
+        }
+        // 网络错误时不记录结果，下次再探测
+        let supported = match self.missing_chunks(&[]).await {
+            Ok(data) => data.is_some(),
+            Err(e) => {
+                log::warn!("[SyncClient] 探测块同步接口失败: {}", e);
+                return false;
+            }
+        };
+        if !supported {
+            log::info!("[SyncClient] 服务端不支持块同步，大文件将整体传输");
+        }
+        if let Ok(mut map) = DELTA_SUPPORT.lock() {
+            map.insert(self.base_url.clone(), supported);
+        }
+        supported
+    }
+
+    /// 检查服务器是否可达（有任意 HTTP 响应即视为可达）
+    pub async fn is_reachable(&self) -> bool {
+        self.http
+            .get(&self.base_url)
+            .timeout(std::time::Duration::from_secs(5))
+            .send()
+            .await
+            .is_ok()
+    }
+
+    /// 分片上传本地文件（init → upload_part → complete），返回使用的分片大小
+    pub async fn upload_file(
+        &self,
+        group_code: &str,
+        asset_type: &str,
+        rel_path: &str,
+        local_path: &Path,
+    ) -> Result<u64, String> {
+        let file_size = std::fs::metadata(local_path)
+            .map_err(|e| format!("无法获取文件元数据: {}", e))?
+            .len();
+        let filename = local_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| rel_path.to_string());
+
+        let init: InitUploadResponse = self
+            .post(
+                "desktop_chunk_upload.php",
+                json!({
+                    "action": "init",
+                    "group_code": group_code,
+                    "project_id": 0,
+                    "asset_type": asset_type,
+                    "rel_path": rel_path,
+                    "filename": filename,
+                    "filesize": file_size,
+                    "mime_type": "application/octet-stream",
+                }),
+            )
+            .await?;
+
+        let mut file = File::open(local_path).map_err(|e| format!("打开文件失败: {}", e))?;
+        let mut limiter = RateLimiter::from_kbps(self.upload_limit_kbps);
+        for part_number in 1..=init.total_parts {
+            let offset = (part_number as u64 - 1) * init.part_size;
+            let chunk_size = file_size.saturating_sub(offset).min(init.part_size) as usize;
+
+            file.seek(SeekFrom::Start(offset))
+                .map_err(|e| format!("文件定位失败: {}", e))?;
+            let mut buffer = vec![0u8; chunk_size];
+            file.read_exact(&mut buffer)
+                .map_err(|e| format!("读取分片 {} 失败: {}", part_number, e))?;
+
+            let form = reqwest::multipart::Form::new()
+                .text("upload_id", init.upload_id.clone())
+                .text("part_number", part_number.to_string())
+                .part("chunk", reqwest::multipart::Part::bytes(buffer).file_name("chunk"));
+
+            let request = self
+                .http
+                .post(format!("{}desktop_chunk_upload.php", self.base_url))
+                .multipart(form);
+            let response = self
+                .authorize(request)
+                .send()
+                .await
+                .map_err(|e| format!("上传分片 {} 失败: {}", part_number, e))?;
+            let _: Value = Self::parse(response)
+                .await
+                .map_err(|e| format!("上传分片 {} 失败: {}", part_number, e))?;
+            rate_limit::throttle(&mut limiter, chunk_size as u64).await;
+        }
+
+        let _: Value = self
+            .post(
+                "desktop_chunk_upload.php",
+                json!({ "action": "complete", "upload_id": init.upload_id }),
+            )
+            .await?;
+        Ok(init.part_size)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MissingChunksResponse {
+    #[serde(default)]
+    missing: Vec<String>,
+}
+
+impl ChunkTransport for SyncClient {
+    async fn missing_chunks(&self, hashes: &[String]) -> Result<Option<Vec<String>>, String> {
+        let request = self
+            .http
+            .post(format!("{}{}", self.base_url, DELTA_ENDPOINT))
+            .header("Content-Type", "application/json")
+            .body(json!({ "action": "missing_chunks", "hashes": hashes }).to_string());
+        let data: Option<MissingChunksResponse> = self.send_optional(request).await?;
+        Ok(data.map(|d| d.missing))
+    }
+
+    async fn put_chunk(&self, hash: &str, data: Vec<u8>) -> Result<(), String> {
+        let form = reqwest::multipart::Form::new()
+            .text("action", "put_chunk")
+            .text("hash", hash.to_string())
+            .part("chunk", reqwest::multipart::Part::bytes(data).file_name("chunk"));
+        let request = self
+            .http
+            .post(format!("{}{}", self.base_url, DELTA_ENDPOINT))
+            .multipart(form);
+        let response = self
+            .authorize(request)
+            .send()
+            .await
+            .map_err(|e| format!("上传块 {} 失败: {}", hash, e))?;
+        let _: Value = Self::parse(response)
+            .await
+            .map_err(|e| format!("上传块 {} 失败: {}", hash, e))?;
+        Ok(())
+    }
+
+    async fn commit_chunks(&self, target: &UploadTarget<'_>, manifest: &ChunkManifest) -> Result<(), String> {
+        let _: Value = self
+            .post(
+                DELTA_ENDPOINT,
+                json!({
+                    "action": "commit",
+                    "group_code": target.group_code,
+                    "project_id": 0,
+                    "asset_type": target.asset_type,
+                    "rel_path": target.rel_path,
+                    "filename": target.filename,
+                    "filesize": manifest.size,
+                    "sha256": manifest.sha256,
+                    "chunks": manifest.chunks,
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn chunk_manifest(&self, storage_key: &str) -> Result<Option<ChunkManifest>, String> {
+        let request = self
+            .http
+            .get(format!("{}{}", self.base_url, DELTA_ENDPOINT))
+            .query(&[("action", "manifest"), ("storage_key", storage_key)]);
+        self.send_optional(request).await
+    }
+
+    async fn get_chunk(&self, hash: &str) -> Result<Vec<u8>, String> {
+        let request = self
+            .http
+            .get(format!("{}{}", self.base_url, DELTA_ENDPOINT))
+            .query(&[("action", "get_chunk"), ("hash", hash)]);
+        let response = self
+            .authorize(request)
+            .send()
+            .await
+            .map_err(|e| format!("下载块 {} 失败: {}", hash, e))?;
+        if !response.status().is_success() {
+            return Err(format!("下载块 {} 失败: HTTP {}", hash, response.status()));
+        }
+        let bytes = response.bytes().await.map_err(|e| format!("下载块 {} 失败: {}", hash, e))?;
+        Ok(bytes.to_vec())
+    }
+}
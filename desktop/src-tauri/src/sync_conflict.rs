@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use crate::downloader;
+use crate::folder_template;
+use crate::file_sync::SyncRule;
+use crate::sync_client::{RemoteResource, SyncClient};
+use crate::sync_engine::{self, SyncEngineConfig};
+use crate::sync_index::SyncIndex;
+use crate::sync_log::{LogAction, PendingLog};
+use crate::sync_roots::{self, SyncRoot};
+use crate::sync_trash::{self, TrashReason};
+
+/// 冲突副本文件名标记（内置忽略规则会跳过带此标记的文件）
+const CONFLICT_MARKER: &str = "冲突副本";
+
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// 本地与服务端都有修改时记录的冲突
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictRecord {
+    pub id: String,
+    /// 对应的同步索引键
+    pub key: String,
+    pub group_code: String,
+    pub asset_type: String,
+    /// 所在文件夹（模板名称）
+    pub folder: String,
+    pub rel_path: String,
+    /// 本地文件（保持原样）
+    pub local_path: String,
+    /// 服务端版本的冲突副本
+    pub copy_path: String,
+    pub remote: RemoteResource,
+    pub detected_at: i64,
+}
+
+/// 冲突处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictResolution {
+    /// 保留本地版本并上传覆盖服务端
+    #[serde(rename = "keep_local")]
+    Local,
+    /// 使用服务端版本，本地版本移入回收站
+    #[serde(rename = "keep_remote")]
+    Remote,
+    /// 两个版本都保留，冲突副本转为普通文件
+    #[serde(rename = "keep_both")]
+    Both,
+}
+
+impl ConflictResolution {
+    fn label(&self) -> &'static str {
+        match self {
+            ConflictResolution::Local => "保留本地版本",
+            ConflictResolution::Remote => "使用服务端版本",
+            ConflictResolution::Both => "保留两个版本",
+        }
+    }
+}
+
+/// 冲突副本路径：`名称 (冲突副本 20240101-120000).扩展名`
+pub fn conflict_copy_path(path: &Path, label: &str) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let suffix = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    path.with_file_name(format!("{} ({} {}){}", stem, label, suffix, ext))
+}
+
+/// 下载服务端版本为冲突副本并记录冲突，已有未处理冲突时返回 false
+pub async fn record_conflict(
+    client: &SyncClient,
+    index: &mut SyncIndex,
+    record: ConflictRecord,
+) -> Result<bool, String> {
+    if index.conflict_for(&record.key).is_some() {
+        return Ok(false);
+    }
+
+    let local_path = Path::new(&record.local_path);
+    let copy_path = Path::new(&record.copy_path);
+    let url = client.download_url(&record.remote.storage_key).await?;
+    let (part_path, _) = downloader::download_to_partial(&url, local_path, client.download_limit_kbps()).await?;
+    std::fs::rename(&part_path, copy_path).map_err(|e| format!("保存冲突副本失败: {}", e))?;
+
+    log::warn!("[SyncConflict] 本地与服务端均有修改，已保存服务端版本: {:?}", copy_path);
+    index.conflicts.insert(record.id.clone(), record);
+    Ok(true)
+}
+
+/// 为本地文件生成冲突记录（副本路径按当前时间命名）
+pub fn new_record(
+    key: &str,
+    group_code: &str,
+    asset_type: &str,
+    folder: &str,
+    rel_path: &str,
+    local_path: &Path,
+    remote: &RemoteResource,
+) -> ConflictRecord {
+    let now = chrono::Local::now();
+    ConflictRecord {
+        id: format!(
+            "{}-{:04}",
+            now.format("%Y%m%d%H%M%S%3f"),
+            SEQUENCE.fetch_add(1, Ordering::SeqCst) % 10000
+        ),
+        key: key.to_string(),
+        group_code: group_code.to_string(),
+        asset_type: asset_type.to_string(),
+        folder: folder.to_string(),
+        rel_path: rel_path.to_string(),
+        local_path: local_path.to_string_lossy().to_string(),
+        copy_path: conflict_copy_path(local_path, CONFLICT_MARKER).to_string_lossy().to_string(),
+        remote: remote.clone(),
+        detected_at: now.timestamp(),
+    }
+}
+
+/// 获取全部根目录中未处理的冲突
+#[tauri::command]
+pub fn list_conflicts() -> Result<Vec<ConflictRecord>, String> {
+    let mut conflicts = Vec::new();
+    for root in sync_roots::all() {
+        let index = SyncIndex::load(Path::new(&root.path))?;
+        conflicts.extend(index.conflicts.into_values());
+    }
+    conflicts.sort_by_key(|c| std::cmp::Reverse(c.detected_at));
+    Ok(conflicts)
+}
+
+/// 按选择处理冲突
+#[tauri::command]
+pub async fn resolve_conflict(id: String, resolution: ConflictResolution) -> Result<(), String> {
+    let config = sync_engine::config().ok_or("同步引擎未配置")?;
+    let _run = sync_engine::begin_run()?;
+
+    // 找到记录该冲突的根目录
+    let (sync_root, mut index) = sync_roots::all()
+        .into_iter()
+        .find_map(|r| {
+            let index = SyncIndex::load(Path::new(&r.path)).ok()?;
+            index.conflicts.contains_key(&id).then_some((r, index))
+        })
+        .ok_or("冲突不存在或已处理")?;
+    let root = PathBuf::from(&sync_root.path);
+    let record = index.conflicts.get(&id).cloned().ok_or("冲突不存在或已处理")?;
+
+    let pending_log = PendingLog::new(&record.group_code, &record.asset_type, &record.rel_path, LogAction::ResolveConflict)
+        .detail(resolution.label());
+    let result = apply_resolution(&config, &sync_root, &root, &mut index, &record, resolution).await;
+    pending_log.finish(0, &result);
+    result?;
+
+    index.conflicts.remove(&id);
+    index.save()?;
+    log::info!("[SyncConflict] 已处理冲突 {} ({:?})", record.rel_path, resolution);
+    Ok(())
+}
+
+async fn apply_resolution(
+    config: &SyncEngineConfig,
+    sync_root: &SyncRoot,
+    root: &Path,
+    index: &mut SyncIndex,
+    record: &ConflictRecord,
+    resolution: ConflictResolution,
+) -> Result<(), String> {
+    let local_path = Path::new(&record.local_path);
+    let copy_path = Path::new(&record.copy_path);
+
+    match resolution {
+        ConflictResolution::Local | ConflictResolution::Both => {
+            let rule = folder_template::current()
+                .rule_for(&format!("{}/{}", record.folder, record.rel_path))
+                .unwrap_or(SyncRule::ManualUpload);
+            if rule != SyncRule::Bidirectional {
+                return Err("该文件夹不允许上传，只能使用服务端版本".to_string());
+            }
+            if !local_path.exists() {
+                return Err("本地文件已不存在".to_string());
+            }
+
+            let client = SyncClient::new(&config.server_url, config.token.clone()).with_limits(sync_root);
+            let part_size = client
+                .upload_file(&record.group_code, &record.asset_type, &record.rel_path, local_path)
+                .await?;
+            let mut entry = sync_engine::index_entry(local_path, None)?;
+            entry.part_size = Some(part_size);
+            index.insert(record.key.clone(), entry);
+
+            if copy_path.exists() {
+                if resolution == ConflictResolution::Both {
+                    // 去掉冲突标记，下次同步作为新文件上传
+                    let kept = conflict_copy_path(local_path, "服务端版本");
+                    std::fs::rename(copy_path, &kept).map_err(|e| format!("重命名冲突副本失败: {}", e))?;
+                } else {
+                    sync_trash::move_to_trash(root, copy_path, TrashReason::ConflictReplaced)?;
+                }
+            }
+        }
+        ConflictResolution::Remote => {
+            if !copy_path.exists() {
+                return Err("冲突副本已不存在".to_string());
+            }
+            if local_path.exists() {
+                sync_trash::move_to_trash(root, local_path, TrashReason::ConflictReplaced)?;
+            }
+            std::fs::rename(copy_path, local_path).map_err(|e| format!("恢复服务端版本失败: {}", e))?;
+            index.insert(record.key.clone(), sync_engine::index_entry(local_path, Some(&record.remote))?);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,747 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use crate::chunker::ChunkManifest;
+use crate::commands::{self, GroupFolder, LocalFile};
+use crate::delta_sync;
+use crate::download_cache::{self, CachePolicy};
+use crate::file_stability::{self, Stability};
+use crate::file_versions::VersionPolicy;
+use crate::file_sync::{SyncRule, SyncStatus, WaitingFile};
+use crate::folder_template::{self, FolderTemplate, FolderTemplateConfig};
+use crate::placeholder::{self, Placeholder};
+use crate::project_archive;
+use crate::s3_etag;
+use crate::scanner;
+use crate::selective_sync::{self, SelectiveSyncSettings, SyncMode};
+use crate::sync_client::{RemoteResource, SyncClient};
+use crate::sync_conflict;
+use crate::sync_guard::{self, ChangeCounts, ChangeKind, GuardConfig};
+use crate::sync_ignore::SyncIgnore;
+use crate::sync_index::{IndexEntry, SyncIndex};
+use crate::sync_log::{LogAction, PendingLog};
+use crate::sync_planner::{self, PlannedAction, SyncAction};
+use crate::sync_queue;
+use crate::sync_rename::{self, DetectedRename};
+use crate::sync_roots::{self, SyncRoot};
+use crate::sync_trash::{self, TrashPolicy, TrashReason};
+
+/// 同步引擎配置（由前端登录后下发）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEngineConfig {
+    pub server_url: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    /// 单个根目录（未在根目录设置中配置多个根目录时使用）
+    #[serde(default)]
+    pub root_dir: String,
+    /// 文件写入完成判定的静默窗口（秒）
+    #[serde(default = "default_quiet_window")]
+    pub quiet_window_secs: u64,
+    /// 批量删除/修改保护
+    #[serde(default)]
+    pub guard: GuardConfig,
+    /// 本地回收站自动清理策略
+    #[serde(default)]
+    pub trash: TrashPolicy,
+    /// 作品文件本地版本保留策略
+    #[serde(default)]
+    pub versions: VersionPolicy,
+    /// 跨项目共享的下载缓存
+    #[serde(default)]
+    pub cache: CachePolicy,
+}
+
+fn default_quiet_window() -> u64 {
+    file_stability::DEFAULT_QUIET_WINDOW_SECS
+}
+
+/// 单次同步结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncSummary {
+    pub uploaded: u32,
+    pub downloaded: u32,
+    pub deleted_local: u32,
+    pub deleted_remote: u32,
+    pub conflicts: u32,
+    pub waiting: u32,
+    /// 因批量变更暂停同步的项目
+    pub paused: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref CONFIG: RwLock<Option<SyncEngineConfig>> = RwLock::new(None);
+    static ref STATUS: Mutex<SyncStatus> = Mutex::new(SyncStatus::default());
+}
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 已有同步在运行时返回的错误
+pub const ALREADY_RUNNING: &str = "同步正在进行中";
+
+/// 当前同步状态
+pub fn status() -> SyncStatus {
+    STATUS.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+fn update_status(app: &AppHandle, f: impl FnOnce(&mut SyncStatus)) {
+    if let Ok(mut status) = STATUS.lock() {
+        f(&mut status);
+        let _ = app.emit("sync-status", status.clone());
+    }
+}
+
+pub fn config() -> Option<SyncEngineConfig> {
+    CONFIG.read().ok().and_then(|c| c.clone())
+}
+
+/// 运行结束时复位运行标记
+pub struct RunGuard;
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 标记同步开始，已有同步在运行时返回错误；返回值释放时复位
+pub fn begin_run() -> Result<RunGuard, String> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(ALREADY_RUNNING.to_string());
+    }
+    Ok(RunGuard)
+}
+
+/// 执行一次完整同步
+pub async fn run_once(app: &AppHandle) -> Result<SyncSummary, String> {
+    let config = config().ok_or("同步引擎未配置")?;
+    let _guard = begin_run()?;
+
+    update_status(app, |s| s.is_syncing = true);
+
+    let result = sync_all_roots(app, &config).await;
+
+    update_status(app, |s| {
+        s.is_syncing = false;
+        if let Ok((summary, waiting)) = &result {
+            s.last_sync = Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+            s.pending_uploads = summary.waiting;
+            s.pending_downloads = 0;
+            s.waiting = waiting.clone();
+        }
+        s.paused_projects = sync_guard::paused_group_codes();
+    });
+
+    result.map(|(summary, _)| summary)
+}
+
+/// 按优先级依次同步启用的根目录，全部失败时返回错误
+///
+/// 先为全部根目录生成计划，统一经过批量保护检查后再执行。
+async fn sync_all_roots(app: &AppHandle, config: &SyncEngineConfig) -> Result<(SyncSummary, Vec<WaitingFile>), String> {
+    let roots = sync_roots::enabled();
+    if roots.is_empty() {
+        return Err("未配置同步根目录".to_string());
+    }
+
+    let mut summary = SyncSummary::default();
+    let mut waiting = Vec::new();
+    let mut claimed = HashSet::new();
+    let mut last_error = None;
+    let mut failed = 0;
+
+    let mut plans = Vec::new();
+    let mut counts: HashMap<String, ChangeCounts> = HashMap::new();
+    for root in &roots {
+        match plan_root(config, root, &mut claimed, &mut counts, &mut summary).await {
+            Ok(plan) => plans.push(plan),
+            Err(e) => {
+                log::error!("[SyncEngine] 同步根目录 {} 失败: {}", root.path, e);
+                summary.errors.push(format!("{}: {}", root.path, e));
+                last_error = Some(e);
+                failed += 1;
+            }
+        }
+    }
+    if failed == roots.len() {
+        return Err(last_error.unwrap_or_default());
+    }
+
+    let blocked = sync_guard::evaluate(app, &config.guard, &counts);
+    for plan in plans {
+        let path = plan.root.to_string_lossy().to_string();
+        if let Err(e) = apply_root(config, plan, &blocked, &mut summary, &mut waiting).await {
+            log::error!("[SyncEngine] 同步根目录 {} 失败: {}", path, e);
+            summary.errors.push(format!("{}: {}", path, e));
+        }
+    }
+
+    summary.waiting = waiting.len() as u32;
+    summary.paused.extend(blocked);
+    summary.paused.sort();
+    summary.paused.dedup();
+    Ok((summary, waiting))
+}
+
+/// 一个根目录的同步计划
+struct RootPlan {
+    root: PathBuf,
+    client: SyncClient,
+    groups: Vec<GroupFolder>,
+    templates: Arc<FolderTemplateConfig>,
+    index: SyncIndex,
+    folders: Vec<FolderPlan>,
+}
+
+/// 一个资源文件夹待执行的重命名和同步操作
+struct FolderPlan {
+    group: usize,
+    template: usize,
+    mode: SyncMode,
+    renames: Vec<DetectedRename>,
+    actions: Vec<PlannedAction>,
+}
+
+impl RootPlan {
+    fn context<'a>(&'a self, folder: &FolderPlan, quiet_window: Duration) -> FolderContext<'a> {
+        let group = &self.groups[folder.group];
+        let template = &self.templates.folders[folder.template];
+        FolderContext {
+            client: &self.client,
+            root: &self.root,
+            group,
+            template,
+            asset_type: template.asset_type.as_deref().unwrap_or_default(),
+            asset_dir: Path::new(&group.path).join(&template.name),
+            quiet_window,
+            mode: folder.mode,
+        }
+    }
+}
+
+/// 统计批量保护关心的变更：删除、覆盖已有文件和重命名
+pub fn count_changes(
+    counts: &mut ChangeCounts,
+    folder: &str,
+    renames: &[DetectedRename],
+    actions: &[PlannedAction],
+) {
+    for rename in renames {
+        counts.modifications += 1;
+        if counts.samples.len() < 10 {
+            counts.samples.push(format!("{}/{} → {}", folder, rename.old_rel_path, rename.new_rel_path));
+        }
+    }
+    for planned in actions {
+        match change_kind(planned) {
+            Some(ChangeKind::Deletion) => counts.deletions += 1,
+            Some(ChangeKind::Modification) => counts.modifications += 1,
+            None => continue,
+        }
+        if counts.samples.len() < 10 {
+            counts.samples.push(format!("{}/{}", folder, planned.rel_path));
+        }
+    }
+}
+
+/// 为一个根目录生成同步计划；claimed 记录已由优先级更高的根目录同步的项目
+async fn plan_root(
+    config: &SyncEngineConfig,
+    sync_root: &SyncRoot,
+    claimed: &mut HashSet<String>,
+    counts: &mut HashMap<String, ChangeCounts>,
+    summary: &mut SyncSummary,
+) -> Result<RootPlan, String> {
+    let root = PathBuf::from(&sync_root.path);
+    if !root.is_dir() {
+        return Err("同步根目录不存在".to_string());
+    }
+
+    let mut groups = scanner::scan_groups(&root)?;
+    groups.retain(|g| {
+        if claimed.insert(g.group_code.clone()) {
+            return true;
+        }
+        log::warn!("[SyncEngine] 项目 {} 在多个根目录中重复，跳过 {}", g.group_code, g.path);
+        summary.errors.push(format!("项目 {} 在多个根目录中重复，已跳过 {}", g.group_code, g.path));
+        false
+    });
+    let mut plan = RootPlan {
+        client: SyncClient::new(&config.server_url, config.token.clone()).with_limits(sync_root),
+        index: SyncIndex::load(&root)?,
+        root,
+        groups,
+        templates: folder_template::current(),
+        folders: Vec::new(),
+    };
+    let settings = selective_sync::current();
+    let quiet_window = Duration::from_secs(config.quiet_window_secs);
+
+    let mut folders = Vec::new();
+    for (group_index, group) in plan.groups.iter().enumerate() {
+        let mode = settings.mode_for(&group.group_code);
+        // 已归档的项目只剩占位文件夹，不参与同步
+        if mode == SyncMode::Excluded || project_archive::is_archived(Path::new(&group.path)) {
+            continue;
+        }
+
+        for (template_index, template) in plan.templates.folders.iter().enumerate() {
+            if template.asset_type.is_none()
+                || template.rule == Some(SyncRule::ManualUpload)
+                || settings.is_excluded(&group.group_code, &template.name)
+            {
+                continue;
+            }
+
+            let mut folder = FolderPlan {
+                group: group_index,
+                template: template_index,
+                mode,
+                renames: Vec::new(),
+                actions: Vec::new(),
+            };
+            let ctx = plan.context(&folder, quiet_window);
+            match plan_folder(&ctx, &plan.index, &settings).await {
+                Ok((renames, actions)) => {
+                    let entry = counts.entry(group.group_code.clone()).or_default();
+                    count_changes(entry, &template.name, &renames, &actions);
+                    folder.renames = renames;
+                    folder.actions = actions;
+                    folders.push(folder);
+                }
+                Err(e) => {
+                    log::error!("[SyncEngine] 同步 {} {} 失败: {}", group.group_code, template.name, e);
+                    summary.errors.push(format!("{} {}: {}", group.group_code, template.name, e));
+                }
+            }
+        }
+    }
+    plan.folders = folders;
+    Ok(plan)
+}
+
+/// 执行一个根目录的同步计划，跳过因批量变更暂停的项目
+async fn apply_root(
+    config: &SyncEngineConfig,
+    mut plan: RootPlan,
+    blocked: &HashSet<String>,
+    summary: &mut SyncSummary,
+    waiting: &mut Vec<WaitingFile>,
+) -> Result<(), String> {
+    let quiet_window = Duration::from_secs(config.quiet_window_secs);
+    let mut index = std::mem::take(&mut plan.index);
+    let mut queued = 0;
+
+    for folder in std::mem::take(&mut plan.folders) {
+        let ctx = plan.context(&folder, quiet_window);
+        let group_code = &ctx.group.group_code;
+        if blocked.contains(group_code) {
+            continue;
+        }
+
+        // 本地重命名交给队列调用服务端重命名，避免删除后重新上传
+        if !folder.renames.is_empty() {
+            let base = index.entries_under(group_code, ctx.asset_type);
+            let count = folder.renames.len();
+            match sync_rename::enqueue(group_code, ctx.asset_type, folder.renames, &base) {
+                Ok(n) => {
+                    queued += n;
+                    for _ in 0..count {
+                        sync_guard::record(group_code, ChangeKind::Modification);
+                    }
+                }
+                Err(e) => summary.errors.push(format!("{} {}: {}", group_code, ctx.template.name, e)),
+            }
+        }
+        apply_actions(&ctx, folder.actions, &mut index, summary, waiting).await;
+    }
+    if queued > 0 {
+        sync_queue::wake();
+    }
+
+    index.save()?;
+    match sync_trash::auto_purge(&plan.root, &config.trash) {
+        Ok(0) => {}
+        Ok(n) => log::info!("[SyncEngine] 回收站自动清理 {} 项", n),
+        Err(e) => log::warn!("[SyncEngine] 回收站清理失败: {}", e),
+    }
+    Ok(())
+}
+
+struct FolderContext<'a> {
+    client: &'a SyncClient,
+    root: &'a Path,
+    group: &'a GroupFolder,
+    template: &'a FolderTemplate,
+    asset_type: &'a str,
+    asset_dir: PathBuf,
+    quiet_window: Duration,
+    mode: SyncMode,
+}
+
+/// 批量保护统计用的变更类型：删除，或覆盖已有文件
+fn change_kind(planned: &PlannedAction) -> Option<ChangeKind> {
+    match planned.action {
+        SyncAction::DeleteLocal | SyncAction::DeleteRemote => Some(ChangeKind::Deletion),
+        SyncAction::Upload if planned.remote.is_some() => Some(ChangeKind::Modification),
+        SyncAction::Download if planned.local.is_some() => Some(ChangeKind::Modification),
+        _ => None,
+    }
+}
+
+/// 资源文件夹的本地状态（已应用忽略规则与选择性同步设置）
+pub struct LocalState {
+    pub ignore: SyncIgnore,
+    pub local: Vec<LocalFile>,
+    pub base: BTreeMap<String, IndexEntry>,
+}
+
+pub fn local_state(
+    asset_dir: &Path,
+    group_code: &str,
+    template: &FolderTemplate,
+    asset_type: &str,
+    index: &SyncIndex,
+    settings: &SelectiveSyncSettings,
+) -> Result<LocalState, String> {
+    // 不同步的子文件夹在本地、服务端和索引三方都跳过，避免被当作删除
+    let ignore = SyncIgnore::for_dir(asset_dir);
+    let local = if asset_dir.is_dir() {
+        scanner::collect_files(asset_dir, asset_dir, &ignore)?
+            .into_iter()
+            .filter(|f| !is_excluded(settings, group_code, template, &f.rel_path))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // 已同步后才加入忽略规则的文件同样跳过，避免被当作本地删除
+    let mut base = index.entries_under(group_code, asset_type);
+    base.retain(|rel_path, _| {
+        !is_excluded(settings, group_code, template, rel_path) && !ignore.is_ignored(&asset_dir.join(rel_path), false)
+    });
+    Ok(LocalState { ignore, local, base })
+}
+
+fn is_excluded(settings: &SelectiveSyncSettings, group_code: &str, template: &FolderTemplate, rel_path: &str) -> bool {
+    settings.is_excluded(
+        group_code,
+        &format!("{}/{}", template.name, sync_planner::normalize_rel_path(rel_path)),
+    )
+}
+
+/// 资源文件夹内按相对路径取同步规则（子文件夹可单独配置）
+pub fn folder_rule(template: &FolderTemplate) -> impl Fn(&str) -> SyncRule {
+    let templates = folder_template::current();
+    let name = template.name.clone();
+    let default_rule = template.rule.unwrap_or(SyncRule::ManualUpload);
+    move |rel_path: &str| {
+        templates
+            .rule_for(&format!("{}/{}", name, rel_path))
+            .unwrap_or(default_rule)
+    }
+}
+
+/// 本地文件与服务端资源内容是否一致：优先比对 SHA-256，其次比对 S3 ETag，无需下载
+///
+/// 服务端未提供分片大小时使用上传该对象时记入索引的分片大小。
+fn same_content(
+    asset_dir: &Path,
+    local: &LocalFile,
+    remote: &RemoteResource,
+    base: Option<&IndexEntry>,
+) -> Option<bool> {
+    if local.size != remote.size {
+        return Some(false);
+    }
+    let path = asset_dir.join(&local.rel_path);
+    if let Some(hash) = &remote.hash {
+        return commands::sha256_file(&path).ok().map(|h| h.eq_ignore_ascii_case(hash));
+    }
+    let part_size = remote.part_size.or(base.and_then(|b| b.part_size));
+    s3_etag::matches(&path, remote.etag.as_deref()?, part_size)
+}
+
+/// 生成资源文件夹的同步计划：识别出的重命名单独返回，相关路径不再生成删除和上传
+async fn plan_folder(
+    ctx: &FolderContext<'_>,
+    index: &SyncIndex,
+    settings: &SelectiveSyncSettings,
+) -> Result<(Vec<DetectedRename>, Vec<PlannedAction>), String> {
+    let group_code = &ctx.group.group_code;
+    let LocalState { ignore, local, base } =
+        local_state(&ctx.asset_dir, group_code, ctx.template, ctx.asset_type, index, settings)?;
+    let rule_for = folder_rule(ctx.template);
+    let renames = sync_rename::detect_renames(group_code, ctx.asset_type, &ctx.asset_dir, &local, &base, &rule_for);
+
+    let remote: Vec<_> = ctx
+        .client
+        .list_resources(group_code, ctx.asset_type)
+        .await?
+        .into_iter()
+        .filter(|r| {
+            !ignore.is_ignored(&ctx.asset_dir.join(&r.rel_path), false)
+                && !is_excluded(settings, group_code, ctx.template, &r.rel_path)
+        })
+        .collect();
+
+    if ctx.mode == SyncMode::MetadataOnly {
+        selective_sync::save_listing(ctx.root, group_code, ctx.asset_type, &remote)?;
+    }
+
+    let mut actions = sync_planner::plan(&local, &remote, &base, &rule_for);
+    // 内容比对需要读取整个文件，在阻塞线程池中执行
+    let asset_dir = ctx.asset_dir.clone();
+    let mut actions = tokio::task::spawn_blocking(move || {
+        sync_planner::refine_by_content(&mut actions, &base, &|l, r| {
+            same_content(&asset_dir, l, r, base.get(&sync_planner::normalize_rel_path(&l.rel_path)))
+        });
+        actions
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    // 离线队列中尚未发送的路径和识别出的重命名由队列处理
+    let queued = sync_queue::pending_paths(group_code, ctx.asset_type);
+    let renamed: HashSet<&str> = renames
+        .iter()
+        .flat_map(|r| [r.old_rel_path.as_str(), r.new_rel_path.as_str()])
+        .collect();
+    if !queued.is_empty() || !renamed.is_empty() {
+        actions.retain(|a| !queued.contains(&a.rel_path) && !renamed.contains(a.rel_path.as_str()));
+    }
+    if ctx.mode == SyncMode::MetadataOnly {
+        // 只同步列表：不下载文件内容，也不删除本地文件
+        actions.retain(|a| {
+            !matches!(a.action, SyncAction::Download | SyncAction::DeleteLocal | SyncAction::Conflict)
+        });
+    }
+    if ctx.mode == SyncMode::OnDemand {
+        // 按需下载：本地没有的文件只创建占位文件，已下载的文件照常更新
+        for a in actions.iter_mut() {
+            if a.action == SyncAction::Download && a.local.is_none() {
+                a.action = SyncAction::Placeholder;
+            }
+        }
+    }
+    Ok((renames, actions))
+}
+
+async fn apply_actions(
+    ctx: &FolderContext<'_>,
+    actions: Vec<PlannedAction>,
+    index: &mut SyncIndex,
+    summary: &mut SyncSummary,
+    waiting: &mut Vec<WaitingFile>,
+) {
+    for planned in actions {
+        let key = SyncIndex::key(&ctx.group.group_code, ctx.asset_type, &planned.rel_path);
+        let path = ctx.asset_dir.join(&planned.rel_path);
+
+        let pending_log = log_action(&planned.action)
+            .map(|action| PendingLog::new(&ctx.group.group_code, ctx.asset_type, &planned.rel_path, action));
+        let result = apply_action(ctx, &planned, &path, &key, index, waiting).await;
+        if let Some(pending_log) = pending_log {
+            if !matches!(result, Ok(false)) {
+                pending_log.finish(transferred_bytes(&planned), &result);
+            }
+        }
+
+        match result {
+            Ok(false) => {}
+            Ok(true) => {
+                if let Some(kind) = change_kind(&planned) {
+                    sync_guard::record(&ctx.group.group_code, kind);
+                }
+                match planned.action {
+                    SyncAction::Upload => summary.uploaded += 1,
+                    SyncAction::Download => summary.downloaded += 1,
+                    SyncAction::DeleteLocal => summary.deleted_local += 1,
+                    SyncAction::DeleteRemote => summary.deleted_remote += 1,
+                    SyncAction::Conflict => summary.conflicts += 1,
+                    _ => {}
+                }
+            }
+            Err(e) => {
+                log::error!("[SyncEngine] {:?} {} 失败: {}", planned.action, planned.rel_path, e);
+                summary.errors.push(format!("{}: {}", planned.rel_path, e));
+            }
+        }
+    }
+}
+
+/// 需要写入同步日志的操作
+fn log_action(action: &SyncAction) -> Option<LogAction> {
+    match action {
+        SyncAction::Upload => Some(LogAction::Upload),
+        SyncAction::Download => Some(LogAction::Download),
+        SyncAction::DeleteLocal => Some(LogAction::DeleteLocal),
+        SyncAction::DeleteRemote => Some(LogAction::DeleteRemote),
+        SyncAction::Conflict => Some(LogAction::Conflict),
+        SyncAction::Record | SyncAction::Forget | SyncAction::Placeholder => None,
+    }
+}
+
+fn transferred_bytes(planned: &PlannedAction) -> u64 {
+    match planned.action {
+        SyncAction::Upload => planned.local.as_ref().map(|f| f.size).unwrap_or(0),
+        SyncAction::Download => planned.remote.as_ref().map(|r| r.size).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+pub fn index_entry(path: &Path, remote: Option<&crate::sync_client::RemoteResource>) -> Result<IndexEntry, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("无法获取文件元数据: {}", e))?;
+    let modified_at = metadata
+        .modified()
+        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+        .unwrap_or(0);
+
+    Ok(IndexEntry {
+        size: metadata.len(),
+        modified_at,
+        storage_key: remote.map(|r| r.storage_key.clone()),
+        remote_size: remote.map(|r| r.size).unwrap_or(metadata.len()),
+        remote_modified_at: remote.and_then(|r| r.modified_at.clone()),
+        synced_at: chrono::Utc::now().timestamp(),
+        file_id: sync_rename::file_id(path),
+        hash: None,
+        chunks: None,
+        part_size: None,
+    })
+}
+
+/// 刚传输过的文件顺带记录内容哈希，供重命名识别
+///
+/// 按块传输的文件直接使用块清单中的哈希，并记录块清单供下次复用。
+pub fn index_entry_with_hash(
+    path: &Path,
+    remote: Option<&crate::sync_client::RemoteResource>,
+    manifest: Option<ChunkManifest>,
+) -> Result<IndexEntry, String> {
+    let mut entry = index_entry(path, remote)?;
+    match manifest {
+        Some(m) => {
+            entry.hash = Some(m.sha256);
+            entry.chunks = Some(m.chunks);
+        }
+        None => entry.hash = commands::sha256_file(path).ok(),
+    }
+    Ok(entry)
+}
+
+async fn apply_action(
+    ctx: &FolderContext<'_>,
+    planned: &PlannedAction,
+    path: &Path,
+    key: &str,
+    index: &mut SyncIndex,
+    waiting: &mut Vec<WaitingFile>,
+) -> Result<bool, String> {
+    match planned.action {
+        SyncAction::Upload => {
+            let stability = file_stability::check(path, ctx.quiet_window);
+            if stability != Stability::Stable {
+                waiting.push(WaitingFile {
+                    path: path.to_string_lossy().to_string(),
+                    reason: stability.description().to_string(),
+                });
+                return Ok(false);
+            }
+
+            let (manifest, part_size) =
+                delta_sync::upload_file(ctx.client, &ctx.group.group_code, ctx.asset_type, &planned.rel_path, path)
+                    .await?;
+            file_stability::forget(path);
+            let mut entry = index_entry_with_hash(path, None, manifest)?;
+            entry.part_size = part_size;
+            index.insert(key.to_string(), entry);
+        }
+        SyncAction::Download => {
+            let remote = planned.remote.as_ref().ok_or("缺少服务端资源")?;
+            let (part_path, manifest) = delta_sync::download_file(ctx.client, remote, path, index.get(key)).await?;
+            // 被覆盖的本地文件先移入回收站
+            if path.exists() {
+                if let Err(e) = sync_trash::move_to_trash(ctx.root, path, TrashReason::RemoteOverwritten) {
+                    let _ = std::fs::remove_file(&part_path);
+                    return Err(e);
+                }
+            }
+            std::fs::rename(&part_path, path).map_err(|e| format!("保存文件失败: {}", e))?;
+            let entry = index_entry_with_hash(path, Some(remote), manifest)?;
+            download_cache::store(remote, path).await;
+            index.insert(key.to_string(), entry);
+        }
+        SyncAction::DeleteLocal => {
+            if planned.local.as_ref().is_some_and(|l| l.placeholder.is_some()) {
+                placeholder::remove(path)?;
+            } else {
+                sync_trash::move_to_trash(ctx.root, path, TrashReason::RemoteDeleted)?;
+            }
+            index.remove(key);
+        }
+        SyncAction::DeleteRemote => {
+            let remote = planned.remote.as_ref().ok_or("缺少服务端资源")?;
+            ctx.client.delete_resource(&ctx.group.group_code, &remote.storage_key).await?;
+            index.remove(key);
+        }
+        SyncAction::Conflict => {
+            let remote = planned.remote.as_ref().ok_or("缺少服务端资源")?;
+            let record = sync_conflict::new_record(
+                key,
+                &ctx.group.group_code,
+                ctx.asset_type,
+                &ctx.template.name,
+                &planned.rel_path,
+                path,
+                remote,
+            );
+            return sync_conflict::record_conflict(ctx.client, index, record).await;
+        }
+        SyncAction::Record => {
+            index.insert(key.to_string(), index_entry(path, planned.remote.as_ref())?);
+            index.remove_conflicts_for(key);
+        }
+        SyncAction::Forget => {
+            index.remove(key);
+            index.remove_conflicts_for(key);
+        }
+        SyncAction::Placeholder => {
+            let remote = planned.remote.as_ref().ok_or("缺少服务端资源")?;
+            placeholder::write(path, &Placeholder::new(&ctx.group.group_code, ctx.asset_type, remote))?;
+            // 占位文件不记入索引，删除占位文件不会删除服务端文件
+            index.remove(key);
+        }
+    }
+    Ok(true)
+}
+
+/// 配置同步引擎（服务器地址、令牌、根目录）
+#[tauri::command]
+pub fn configure_sync_engine(config: SyncEngineConfig) -> Result<(), String> {
+    if config.server_url.trim().is_empty() {
+        return Err("未配置服务器地址".to_string());
+    }
+    if !config.root_dir.is_empty() && !Path::new(&config.root_dir).is_dir() {
+        return Err("同步根目录不存在".to_string());
+    }
+    *CONFIG.write().map_err(|e| e.to_string())? = Some(config);
+    Ok(())
+}
+
+/// 清除同步引擎配置（退出登录时调用），之后的同步会因未配置而跳过
+#[tauri::command]
+pub fn reset_sync_engine() -> Result<(), String> {
+    *CONFIG.write().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+/// 立即执行一次同步
+#[tauri::command]
+pub async fn run_sync(app: AppHandle) -> Result<SyncSummary, String> {
+    run_once(&app).await
+}
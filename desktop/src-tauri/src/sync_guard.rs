@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+/// 批量删除/修改保护阈值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 统计时间窗口（秒）
+    #[serde(default = "default_window")]
+    pub window_secs: i64,
+    #[serde(default = "default_project_deletions")]
+    pub project_max_deletions: u32,
+    #[serde(default = "default_project_modifications")]
+    pub project_max_modifications: u32,
+    #[serde(default = "default_global_deletions")]
+    pub global_max_deletions: u32,
+    #[serde(default = "default_global_modifications")]
+    pub global_max_modifications: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+fn default_window() -> i64 {
+    600
+}
+fn default_project_deletions() -> u32 {
+    20
+}
+fn default_project_modifications() -> u32 {
+    100
+}
+fn default_global_deletions() -> u32 {
+    50
+}
+fn default_global_modifications() -> u32 {
+    300
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: default_window(),
+            project_max_deletions: default_project_deletions(),
+            project_max_modifications: default_project_modifications(),
+            global_max_deletions: default_global_deletions(),
+            global_max_modifications: default_global_modifications(),
+        }
+    }
+}
+
+/// 一个项目待执行的批量变更统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChangeCounts {
+    pub deletions: u32,
+    pub modifications: u32,
+    /// 部分受影响文件，供确认时展示
+    pub samples: Vec<String>,
+}
+
+/// 因超出阈值而暂停的项目
+#[derive(Debug, Clone, Serialize)]
+pub struct PausedProject {
+    pub group_code: String,
+    pub deletions: u32,
+    pub modifications: u32,
+    pub samples: Vec<String>,
+    pub reason: String,
+    pub detected_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Deletion,
+    Modification,
+}
+
+struct GuardState {
+    /// 已执行的变更记录（时间戳、群编号、类型）
+    history: VecDeque<(i64, String, ChangeKind)>,
+    paused: HashMap<String, PausedProject>,
+    /// 已确认、下次同步允许执行的项目
+    approved: HashSet<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref STATE: Mutex<GuardState> = Mutex::new(GuardState {
+        history: VecDeque::new(),
+        paused: HashMap::new(),
+        approved: HashSet::new(),
+    });
+}
+
+fn recent_counts(state: &mut GuardState, window_secs: i64, now: i64) -> HashMap<String, (u32, u32)> {
+    while state.history.front().is_some_and(|(ts, _, _)| now - ts > window_secs) {
+        state.history.pop_front();
+    }
+
+    let mut counts: HashMap<String, (u32, u32)> = HashMap::new();
+    for (_, group_code, kind) in &state.history {
+        let entry = counts.entry(group_code.clone()).or_default();
+        match kind {
+            ChangeKind::Deletion => entry.0 += 1,
+            ChangeKind::Modification => entry.1 += 1,
+        }
+    }
+    counts
+}
+
+/// 检查本次同步计划（全部根目录的全部项目），返回需要暂停的项目
+///
+/// 计数包含时间窗口内已执行的变更，全局计数包含所有项目的历史；已确认的项目本次放行。
+pub fn evaluate(app: &AppHandle, config: &GuardConfig, planned: &HashMap<String, ChangeCounts>) -> HashSet<String> {
+    let mut blocked = HashSet::new();
+    if !config.enabled {
+        return blocked;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut state = match STATE.lock() {
+        Ok(s) => s,
+        Err(_) => return blocked,
+    };
+    let recent = recent_counts(&mut state, config.window_secs, now);
+
+    let total = |group_code: &str, counts: &ChangeCounts| {
+        let (d, m) = recent.get(group_code).copied().unwrap_or_default();
+        (counts.deletions + d, counts.modifications + m)
+    };
+
+    let (recent_deletions, recent_modifications) =
+        recent.values().fold((0, 0), |acc, (d, m)| (acc.0 + d, acc.1 + m));
+    let (global_deletions, global_modifications) = planned.values().fold(
+        (recent_deletions, recent_modifications),
+        |acc, counts| (acc.0 + counts.deletions, acc.1 + counts.modifications),
+    );
+    let global_exceeded = global_deletions > config.global_max_deletions
+        || global_modifications > config.global_max_modifications;
+
+    let mut newly_paused = Vec::new();
+    for (group_code, counts) in planned {
+        if counts.deletions == 0 && counts.modifications == 0 {
+            // 变更已被撤销，解除暂停
+            state.paused.remove(group_code);
+            state.approved.remove(group_code);
+            continue;
+        }
+        if state.approved.remove(group_code) {
+            state.paused.remove(group_code);
+            continue;
+        }
+
+        let (deletions, modifications) = total(group_code, counts);
+        let reason = if deletions > config.project_max_deletions {
+            format!("{} 分钟内删除 {} 个文件，超过阈值 {}", config.window_secs / 60, deletions, config.project_max_deletions)
+        } else if modifications > config.project_max_modifications {
+            format!("{} 分钟内修改 {} 个文件，超过阈值 {}", config.window_secs / 60, modifications, config.project_max_modifications)
+        } else if global_exceeded {
+            format!("全部项目共删除 {} 个、修改 {} 个文件，超过全局阈值", global_deletions, global_modifications)
+        } else if state.paused.contains_key(group_code) {
+            // 未确认前保持暂停
+            blocked.insert(group_code.clone());
+            continue;
+        } else {
+            continue;
+        };
+
+        blocked.insert(group_code.clone());
+        let paused = PausedProject {
+            group_code: group_code.clone(),
+            deletions: counts.deletions,
+            modifications: counts.modifications,
+            samples: counts.samples.clone(),
+            reason,
+            detected_at: now,
+        };
+        if !state.paused.contains_key(group_code) {
+            newly_paused.push(paused.clone());
+        }
+        state.paused.insert(group_code.clone(), paused);
+    }
+    drop(state);
+
+    for paused in newly_paused {
+        log::warn!("[SyncGuard] 暂停项目 {} 同步: {}", paused.group_code, paused.reason);
+        let _ = app
+            .notification()
+            .builder()
+            .title("同步已暂停")
+            .body(format!("项目 {} {}，请确认后继续同步", paused.group_code, paused.reason))
+            .show();
+        let _ = app.emit("sync-guard-paused", paused);
+    }
+
+    blocked
+}
+
+/// 记录已执行的变更
+pub fn record(group_code: &str, kind: ChangeKind) {
+    if let Ok(mut state) = STATE.lock() {
+        state
+            .history
+            .push_back((chrono::Utc::now().timestamp(), group_code.to_string(), kind));
+    }
+}
+
+pub fn paused_group_codes() -> Vec<String> {
+    STATE
+        .lock()
+        .map(|s| {
+            let mut codes: Vec<String> = s.paused.keys().cloned().collect();
+            codes.sort();
+            codes
+        })
+        .unwrap_or_default()
+}
+
+/// 获取因批量变更而暂停的项目
+#[tauri::command]
+pub fn get_paused_projects() -> Result<Vec<PausedProject>, String> {
+    let state = STATE.lock().map_err(|e| e.to_string())?;
+    let mut projects: Vec<PausedProject> = state.paused.values().cloned().collect();
+    projects.sort_by(|a, b| a.group_code.cmp(&b.group_code));
+    Ok(projects)
+}
+
+/// 确认项目的批量变更，下次同步时执行
+#[tauri::command]
+pub fn confirm_sync_changes(group_code: String) -> Result<(), String> {
+    let mut state = STATE.lock().map_err(|e| e.to_string())?;
+    if !state.paused.contains_key(&group_code) {
+        return Err("该项目没有待确认的变更".to_string());
+    }
+    state.approved.insert(group_code);
+    Ok(())
+}
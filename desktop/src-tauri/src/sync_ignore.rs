@@ -0,0 +1,138 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::sync_roots;
+
+/// 忽略规则文件名（gitignore 语法），可放在根目录、项目目录及任意子目录
+pub const IGNORE_FILE_NAME: &str = ".syncignore";
+
+/// 内置忽略规则：软件自动备份、Office 临时文件、系统缓存文件、同步临时文件和冲突副本
+const DEFAULT_PATTERNS: &[&str] = &[
+    "*.blend[0-9]",
+    "*.blend@",
+    "~$*",
+    ".~lock.*#",
+    "*.tmp",
+    "*.temp",
+    "Thumbs.db",
+    "desktop.ini",
+    ".DS_Store",
+    "autoback/",
+    "AutoBackup*.max",
+    "*.max.bak",
+    "*.sync-part",
+    "* (冲突副本 *)*",
+];
+
+lazy_static::lazy_static! {
+    static ref DEFAULT_RULES: Arc<Gitignore> = Arc::new(build_defaults());
+}
+
+fn build_defaults() -> Gitignore {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in DEFAULT_PATTERNS {
+        if let Err(e) = builder.add_line(None, pattern) {
+            log::warn!("[SyncIgnore] 内置规则 {} 无效: {}", pattern, e);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// 合并后的忽略规则：内置规则 + 从同步根目录到当前目录各级的 .syncignore
+#[derive(Clone)]
+pub struct SyncIgnore {
+    /// 同步根目录，规则只匹配其下的相对路径
+    base: PathBuf,
+    layers: Vec<(PathBuf, Arc<Gitignore>)>,
+}
+
+impl SyncIgnore {
+    /// 加载作用于 dir 的规则；dir 不在任何同步根目录内时以 dir 本身为根
+    pub fn for_dir(dir: &Path) -> Self {
+        match sync_roots::root_of(dir) {
+            Some(root) => Self::within(Path::new(&root.path), dir),
+            None => Self::within(dir, dir),
+        }
+    }
+
+    /// 以 base 为同步根目录加载作用于 dir 的规则，不读取 base 以外的 .syncignore
+    pub fn within(base: &Path, dir: &Path) -> Self {
+        let mut rules = Self { base: base.to_path_buf(), layers: Vec::new() };
+        rules.push_dir(base);
+        if let Ok(rel) = dir.strip_prefix(base) {
+            let mut current = base.to_path_buf();
+            for component in rel.components() {
+                current.push(component);
+                rules.push_dir(&current);
+            }
+        }
+        rules
+    }
+
+    /// 进入子目录时叠加该目录下的 .syncignore
+    pub fn with_child(&self, dir: &Path) -> Self {
+        let mut rules = self.clone();
+        rules.push_dir(dir);
+        rules
+    }
+
+    fn push_dir(&mut self, dir: &Path) {
+        let file = dir.join(IGNORE_FILE_NAME);
+        if !file.is_file() {
+            return;
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        if let Some(e) = builder.add(&file) {
+            log::warn!("[SyncIgnore] 读取 {:?} 失败: {}", file, e);
+        }
+        match builder.build() {
+            Ok(gi) => self.layers.push((dir.to_path_buf(), Arc::new(gi))),
+            Err(e) => log::warn!("[SyncIgnore] 解析 {:?} 失败: {}", file, e),
+        }
+    }
+
+    /// 判断路径是否被忽略，内层目录的规则优先（可用 `!pattern` 取消外层规则）；根目录以外的路径不忽略
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let rel = match path.strip_prefix(&self.base) {
+            Ok(rel) if !rel.as_os_str().is_empty() => rel,
+            _ => return false,
+        };
+
+        for (root, gi) in self.layers.iter().rev() {
+            let rel = match path.strip_prefix(root) {
+                Ok(rel) if !rel.as_os_str().is_empty() => rel,
+                _ => continue,
+            };
+            match gi.matched_path_or_any_parents(rel, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+
+        // 内置规则与位置无关，按根目录下的相对路径分量匹配
+        DEFAULT_RULES.matched_path_or_any_parents(rel, is_dir).is_ignore()
+    }
+}
+
+/// 同步根目录 root 下的路径是否被忽略（逐级读取 root 到其上级目录的 .syncignore）
+pub fn is_ignored_in(root: &Path, path: &Path) -> bool {
+    match path.parent() {
+        Some(parent) => SyncIgnore::within(root, parent).is_ignored(path, path.is_dir()),
+        None => false,
+    }
+}
+
+/// 检查路径是否会被同步忽略
+#[tauri::command]
+pub fn is_ignored(path: String) -> bool {
+    let path = Path::new(&path);
+    let is_dir = path.is_dir();
+    let rules = match path.parent() {
+        Some(parent) => SyncIgnore::for_dir(parent),
+        None => return false,
+    };
+    rules.is_ignored(path, is_dir)
+}
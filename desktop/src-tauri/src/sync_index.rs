@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::chunker::ChunkRef;
+use crate::sync_conflict::ConflictRecord;
+
+/// 根目录下存放同步元数据的目录（以 . 开头，扫描时自动跳过）
+pub const META_DIR_NAME: &str = ".sync";
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// 上次同步成功时的文件状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub size: u64,
+    pub modified_at: u64,
+    #[serde(default)]
+    pub storage_key: Option<String>,
+    #[serde(default)]
+    pub remote_size: u64,
+    #[serde(default)]
+    pub remote_modified_at: Option<String>,
+    pub synced_at: i64,
+    /// 文件系统标识（inode / NTFS 文件索引），用于识别重命名
+    #[serde(default)]
+    pub file_id: Option<u64>,
+    /// 内容 SHA-256（上传或下载时记录）
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// 按块传输的大文件的块清单，下次传输时复用未变化的块
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<Vec<ChunkRef>>,
+    /// 分片上传时使用的分片大小，用于与服务端 ETag 比对
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part_size: Option<u64>,
+}
+
+/// 本地同步索引，每个根目录一份
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncIndex {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    pub entries: BTreeMap<String, IndexEntry>,
+    /// 未处理的冲突（键为冲突 id）
+    #[serde(default)]
+    pub conflicts: BTreeMap<String, ConflictRecord>,
+}
+
+impl SyncIndex {
+    /// 索引键：群编号/资源类型/相对路径
+    pub fn key(group_code: &str, asset_type: &str, rel_path: &str) -> String {
+        format!("{}/{}/{}", group_code, asset_type, rel_path)
+    }
+
+    pub fn meta_dir(root: &Path) -> PathBuf {
+        root.join(META_DIR_NAME)
+    }
+
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = Self::meta_dir(root).join(INDEX_FILE_NAME);
+        let mut index = if path.exists() {
+            let content = fs::read_to_string(&path).map_err(|e| format!("读取同步索引失败: {}", e))?;
+            serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("[SyncIndex] 索引损坏，重新建立: {}", e);
+                SyncIndex::default()
+            })
+        } else {
+            SyncIndex::default()
+        };
+        index.path = path;
+        Ok(index)
+    }
+
+    /// 先写临时文件再替换，避免中断时损坏索引
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let content = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, content).map_err(|e| format!("写入同步索引失败: {}", e))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("保存同步索引失败: {}", e))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&IndexEntry> {
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: String, entry: IndexEntry) {
+        self.entries.insert(key, entry);
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<IndexEntry> {
+        self.entries.remove(key)
+    }
+
+    /// 某个群/资源类型下的全部条目（键为相对路径）
+    pub fn entries_under(&self, group_code: &str, asset_type: &str) -> BTreeMap<String, IndexEntry> {
+        let prefix = format!("{}/{}/", group_code, asset_type);
+        self.entries
+            .range(prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, v)| (k[prefix.len()..].to_string(), v.clone()))
+            .collect()
+    }
+
+    /// 索引键对应的未处理冲突
+    pub fn conflict_for(&self, key: &str) -> Option<&ConflictRecord> {
+        self.conflicts.values().find(|c| c.key == key)
+    }
+
+    pub fn remove_conflicts_for(&mut self, key: &str) {
+        self.conflicts.retain(|_, c| c.key != key);
+    }
+}
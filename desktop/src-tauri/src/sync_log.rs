@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+const LOG_FILE_NAME: &str = "sync_log.jsonl";
+const ROTATED_FILE_NAME: &str = "sync_log.1.jsonl";
+/// 超过该大小时轮转，只保留一份旧日志
+const MAX_LOG_BYTES: u64 = 20 * 1024 * 1024;
+const DEFAULT_LIMIT: usize = 200;
+
+/// 同步操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogAction {
+    Upload,
+    Download,
+    DeleteLocal,
+    DeleteRemote,
+    Conflict,
+    Rename,
+    Move,
+    ResolveConflict,
+}
+
+impl LogAction {
+    fn label(&self) -> &'static str {
+        match self {
+            LogAction::Upload => "上传",
+            LogAction::Download => "下载",
+            LogAction::DeleteLocal => "删除本地",
+            LogAction::DeleteRemote => "删除服务端",
+            LogAction::Conflict => "冲突",
+            LogAction::Rename => "重命名",
+            LogAction::Move => "移动",
+            LogAction::ResolveConflict => "处理冲突",
+        }
+    }
+}
+
+/// 执行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogOutcome {
+    Success,
+    Failed,
+}
+
+/// 一条同步记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncLogEntry {
+    /// 本地时间 YYYY-MM-DD HH:MM:SS
+    pub timestamp: String,
+    /// 项目编号
+    pub project: String,
+    pub asset_type: String,
+    /// 资源文件夹内的相对路径
+    pub path: String,
+    pub action: LogAction,
+    #[serde(default)]
+    pub bytes: u64,
+    #[serde(default)]
+    pub duration_ms: u64,
+    pub outcome: LogOutcome,
+    /// 附加信息：重命名的原路径、冲突处理方式等
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 开始计时的一条记录，完成时调用 finish 写入日志
+pub struct PendingLog {
+    project: String,
+    asset_type: String,
+    path: String,
+    action: LogAction,
+    detail: Option<String>,
+    started: Instant,
+}
+
+impl PendingLog {
+    pub fn new(project: &str, asset_type: &str, path: &str, action: LogAction) -> Self {
+        Self {
+            project: project.to_string(),
+            asset_type: asset_type.to_string(),
+            path: path.to_string(),
+            action,
+            detail: None,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn finish<T>(self, bytes: u64, result: &Result<T, String>) {
+        record(SyncLogEntry {
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            project: self.project,
+            asset_type: self.asset_type,
+            path: self.path,
+            action: self.action,
+            bytes,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            outcome: if result.is_ok() { LogOutcome::Success } else { LogOutcome::Failed },
+            detail: self.detail,
+            error: result.as_ref().err().cloned(),
+        });
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref LOG_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// 设置日志目录（应用数据目录）
+pub fn init(data_dir: PathBuf) {
+    if let Err(e) = fs::create_dir_all(&data_dir) {
+        log::warn!("[SyncLog] 创建日志目录失败: {}", e);
+    }
+    if let Ok(mut dir) = LOG_DIR.lock() {
+        *dir = Some(data_dir);
+    }
+}
+
+/// 追加一条记录，写入失败只打印警告
+pub fn record(entry: SyncLogEntry) {
+    let line = match serde_json::to_string(&entry) {
+        Ok(l) => l,
+        Err(_) => return,
+    };
+    let dir = match LOG_DIR.lock() {
+        Ok(d) => d,
+        Err(_) => return,
+    };
+    let dir = match dir.as_ref() {
+        Some(d) => d,
+        None => return,
+    };
+
+    let path = dir.join(LOG_FILE_NAME);
+    if fs::metadata(&path).map(|m| m.len() > MAX_LOG_BYTES).unwrap_or(false) {
+        let _ = fs::rename(&path, dir.join(ROTATED_FILE_NAME));
+    }
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(e) = result {
+        log::warn!("[SyncLog] 写入日志失败: {}", e);
+    }
+}
+
+/// 查询条件，日期为 YYYY-MM-DD（含当天）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SyncLogFilter {
+    pub project: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub outcome: Option<LogOutcome>,
+    pub action: Option<LogAction>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+impl SyncLogFilter {
+    fn matches(&self, entry: &SyncLogEntry) -> bool {
+        let date = entry.timestamp.get(..10).unwrap_or("");
+        self.project.as_ref().is_none_or(|p| p.is_empty() || &entry.project == p)
+            && self.start_date.as_ref().is_none_or(|d| date >= d.as_str())
+            && self.end_date.as_ref().is_none_or(|d| date <= d.as_str())
+            && self.outcome.is_none_or(|o| entry.outcome == o)
+            && self.action.is_none_or(|a| entry.action == a)
+    }
+}
+
+/// 分页查询结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncLogPage {
+    pub total: usize,
+    pub items: Vec<SyncLogEntry>,
+}
+
+fn read_entries(path: &Path, filter: &SyncLogFilter, out: &mut Vec<SyncLogEntry>) {
+    let file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return,
+    };
+    // 按字节分行，跳过写入中断导致的残缺行（可能截断在多字节字符中间）
+    out.extend(
+        BufReader::new(file)
+            .split(b'\n')
+            .filter_map(|line| line.ok())
+            .filter_map(|line| serde_json::from_slice::<SyncLogEntry>(&line).ok())
+            .filter(|entry| filter.matches(entry)),
+    );
+}
+
+/// 按时间倒序返回所有匹配的记录
+fn matching(filter: &SyncLogFilter) -> Result<Vec<SyncLogEntry>, String> {
+    let dir = LOG_DIR
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("同步日志未初始化")?;
+
+    let mut entries = Vec::new();
+    read_entries(&dir.join(ROTATED_FILE_NAME), filter, &mut entries);
+    read_entries(&dir.join(LOG_FILE_NAME), filter, &mut entries);
+    entries.reverse();
+    Ok(entries)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 查询同步日志
+#[tauri::command]
+pub async fn query_sync_log(filter: Option<SyncLogFilter>) -> Result<SyncLogPage, String> {
+    tokio::task::spawn_blocking(move || query_blocking(filter.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn query_blocking(filter: SyncLogFilter) -> Result<SyncLogPage, String> {
+    let entries = matching(&filter)?;
+    let total = entries.len();
+    let items = entries
+        .into_iter()
+        .skip(filter.offset)
+        .take(filter.limit.unwrap_or(DEFAULT_LIMIT))
+        .collect();
+    Ok(SyncLogPage { total, items })
+}
+
+/// 将匹配的同步日志导出为 CSV，返回导出条数（忽略分页参数）
+#[tauri::command]
+pub async fn export_sync_log_csv(filter: Option<SyncLogFilter>, save_path: String) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || export_blocking(filter.unwrap_or_default(), &save_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn export_blocking(filter: SyncLogFilter, save_path: &str) -> Result<usize, String> {
+    let entries = matching(&filter)?;
+
+    // 带 BOM 以便 Excel 正确识别中文
+    let mut csv = String::from("\u{feff}时间,项目,资源类型,路径,操作,字节数,耗时(ms),结果,附加信息,错误\n");
+    for entry in &entries {
+        let row = [
+            entry.timestamp.clone(),
+            entry.project.clone(),
+            entry.asset_type.clone(),
+            entry.path.clone(),
+            entry.action.label().to_string(),
+            entry.bytes.to_string(),
+            entry.duration_ms.to_string(),
+            match entry.outcome {
+                LogOutcome::Success => "成功".to_string(),
+                LogOutcome::Failed => "失败".to_string(),
+            },
+            entry.detail.clone().unwrap_or_default(),
+            entry.error.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    fs::write(save_path, csv).map_err(|e| format!("导出失败: {}", e))?;
+    Ok(entries.len())
+}
@@ -0,0 +1,180 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::commands::LocalFile;
+use crate::file_sync::SyncRule;
+use crate::sync_client::RemoteResource;
+use crate::sync_index::IndexEntry;
+
+/// 同步动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    Upload,
+    Download,
+    DeleteLocal,
+    DeleteRemote,
+    /// 本地与服务端自上次同步后都有修改
+    Conflict,
+    /// 两端一致，只更新索引
+    Record,
+    /// 两端都已不存在，清除索引
+    Forget,
+    /// 创建或更新云端占位文件（按需下载的项目）
+    Placeholder,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedAction {
+    pub action: SyncAction,
+    pub rel_path: String,
+    pub local: Option<LocalFile>,
+    pub remote: Option<RemoteResource>,
+}
+
+fn local_changed(local: &LocalFile, base: &IndexEntry) -> bool {
+    local.size != base.size || local.modified_at != base.modified_at
+}
+
+fn remote_changed(remote: &RemoteResource, base: &IndexEntry) -> bool {
+    remote.size != base.remote_size
+        || (base.remote_modified_at.is_some() && remote.modified_at != base.remote_modified_at)
+}
+
+/// 统一使用 / 作为相对路径分隔符
+pub fn normalize_rel_path(rel_path: &str) -> String {
+    rel_path.replace('\\', "/")
+}
+
+/// 根据本地文件、服务端资源和上次同步索引生成同步计划
+///
+/// rule_for 按相对路径返回同步规则（支持子文件夹单独配置）。
+pub fn plan(
+    local: &[LocalFile],
+    remote: &[RemoteResource],
+    base: &BTreeMap<String, IndexEntry>,
+    rule_for: &dyn Fn(&str) -> SyncRule,
+) -> Vec<PlannedAction> {
+    let local_map: BTreeMap<String, &LocalFile> = local
+        .iter()
+        .filter(|f| !f.is_dir)
+        .map(|f| (normalize_rel_path(&f.rel_path), f))
+        .collect();
+    let remote_map: BTreeMap<String, &RemoteResource> = remote
+        .iter()
+        .map(|r| (normalize_rel_path(&r.rel_path), r))
+        .collect();
+
+    let paths: BTreeSet<&String> = local_map.keys().chain(remote_map.keys()).chain(base.keys()).collect();
+    let mut actions = Vec::new();
+
+    for rel_path in paths {
+        let l = local_map.get(rel_path).copied();
+        let r = remote_map.get(rel_path).copied();
+        let b = base.get(rel_path);
+
+        // 云端占位文件只跟随服务端更新或删除，从不上传
+        if let Some(l) = l.filter(|l| l.placeholder.is_some()) {
+            let action = match r {
+                None => Some(SyncAction::DeleteLocal),
+                Some(r) if l.placeholder.as_deref() != Some(r.storage_key.as_str()) => Some(SyncAction::Placeholder),
+                Some(_) => None,
+            };
+            if let Some(action) = action {
+                actions.push(PlannedAction {
+                    action,
+                    rel_path: rel_path.clone(),
+                    local: Some(l.clone()),
+                    remote: r.cloned(),
+                });
+            }
+            continue;
+        }
+
+        let rule = rule_for(rel_path);
+        let can_upload = rule == SyncRule::Bidirectional;
+        let can_download = rule != SyncRule::ManualUpload;
+
+        let action = match (l, r, b) {
+            (Some(_), None, None) if can_upload => Some(SyncAction::Upload),
+            (Some(l), None, Some(b)) => {
+                if local_changed(l, b) {
+                    can_upload.then_some(SyncAction::Upload)
+                } else if b.storage_key.is_some() && can_download {
+                    // 曾在服务端出现过，现已删除
+                    Some(SyncAction::DeleteLocal)
+                } else {
+                    // 刚上传，服务端列表尚未出现
+                    None
+                }
+            }
+            (None, Some(_), None) if can_download => Some(SyncAction::Download),
+            (None, Some(r), Some(b)) => {
+                if can_upload && !remote_changed(r, b) {
+                    Some(SyncAction::DeleteRemote)
+                } else if can_download {
+                    Some(SyncAction::Download)
+                } else {
+                    None
+                }
+            }
+            (None, None, Some(_)) => Some(SyncAction::Forget),
+            (Some(l), Some(r), None) => {
+                if l.size == r.size {
+                    Some(SyncAction::Record)
+                } else if can_download || can_upload {
+                    Some(SyncAction::Conflict)
+                } else {
+                    None
+                }
+            }
+            (Some(l), Some(r), Some(b)) => match (local_changed(l, b), remote_changed(r, b)) {
+                (false, false) if b.storage_key.as_deref() != Some(r.storage_key.as_str()) => {
+                    Some(SyncAction::Record)
+                }
+                (false, false) => None,
+                (true, false) => can_upload.then_some(SyncAction::Upload),
+                (false, true) => can_download.then_some(SyncAction::Download),
+                (true, true) => Some(SyncAction::Conflict),
+            },
+            _ => None,
+        };
+
+        if let Some(action) = action {
+            actions.push(PlannedAction {
+                action,
+                rel_path: rel_path.clone(),
+                local: l.cloned(),
+                remote: r.cloned(),
+            });
+        }
+    }
+
+    actions
+}
+
+/// 用内容比对修正计划：两端内容相同的上传、下载和冲突只更新索引，首次出现且大小相同但内容不同的文件按冲突处理
+///
+/// same_content 比对本地文件与服务端资源（如 SHA-256 或 S3 ETag），无法判断时返回 None。
+pub fn refine_by_content(
+    actions: &mut [PlannedAction],
+    base: &BTreeMap<String, IndexEntry>,
+    same_content: &dyn Fn(&LocalFile, &RemoteResource) -> Option<bool>,
+) {
+    for planned in actions.iter_mut() {
+        let (l, r) = match (&planned.local, &planned.remote) {
+            (Some(l), Some(r)) if l.placeholder.is_none() => (l, r),
+            _ => continue,
+        };
+        planned.action = match planned.action {
+            SyncAction::Upload | SyncAction::Download | SyncAction::Conflict
+                if same_content(l, r) == Some(true) =>
+            {
+                SyncAction::Record
+            }
+            SyncAction::Record if !base.contains_key(&planned.rel_path) && same_content(l, r) == Some(false) => {
+                SyncAction::Conflict
+            }
+            action => action,
+        };
+    }
+}
@@ -0,0 +1,577 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+use crate::delta_sync;
+use crate::folder_template;
+use crate::sync_client::SyncClient;
+use crate::sync_engine;
+use crate::sync_index::SyncIndex;
+use crate::sync_log::{LogAction, PendingLog};
+use crate::sync_planner::normalize_rel_path;
+use crate::sync_roots::{self, SyncRoot};
+
+const QUEUE_FILE_NAME: &str = "queue.json";
+/// 同一操作失败次数达到上限后移入失败列表
+const MAX_ATTEMPTS: u32 = 5;
+/// 队列非空时检查服务器连通性的间隔（秒）
+const CHECK_INTERVAL_SECS: u64 = 30;
+
+/// 待发送到服务端的操作（路径均相对资源文件夹）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncOperation {
+    Upload {
+        group_code: String,
+        asset_type: String,
+        rel_path: String,
+    },
+    Rename {
+        group_code: String,
+        asset_type: String,
+        old_rel_path: String,
+        new_rel_path: String,
+    },
+    Delete {
+        group_code: String,
+        asset_type: String,
+        rel_path: String,
+    },
+    /// 整个目录重命名/移动，按目录批量处理
+    RenameDirectory {
+        group_code: String,
+        asset_type: String,
+        old_dir: String,
+        new_dir: String,
+    },
+}
+
+impl SyncOperation {
+    fn scope(&self) -> (&str, &str) {
+        match self {
+            SyncOperation::Upload { group_code, asset_type, .. }
+            | SyncOperation::Rename { group_code, asset_type, .. }
+            | SyncOperation::Delete { group_code, asset_type, .. }
+            | SyncOperation::RenameDirectory { group_code, asset_type, .. } => (group_code, asset_type),
+        }
+    }
+
+    /// 操作涉及的相对路径，目录以 / 结尾
+    fn paths(&self) -> Vec<String> {
+        match self {
+            SyncOperation::Upload { rel_path, .. } | SyncOperation::Delete { rel_path, .. } => vec![rel_path.clone()],
+            SyncOperation::Rename { old_rel_path, new_rel_path, .. } => {
+                vec![old_rel_path.clone(), new_rel_path.clone()]
+            }
+            SyncOperation::RenameDirectory { old_dir, new_dir, .. } => {
+                vec![format!("{}/", old_dir), format!("{}/", new_dir)]
+            }
+        }
+    }
+
+    /// 对应的同步日志记录
+    fn log(&self) -> PendingLog {
+        let (group_code, asset_type) = self.scope();
+        match self {
+            SyncOperation::Upload { rel_path, .. } => PendingLog::new(group_code, asset_type, rel_path, LogAction::Upload),
+            SyncOperation::Delete { rel_path, .. } => {
+                PendingLog::new(group_code, asset_type, rel_path, LogAction::DeleteRemote)
+            }
+            SyncOperation::Rename { old_rel_path, new_rel_path, .. } => {
+                let action = if Path::new(old_rel_path).parent() == Path::new(new_rel_path).parent() {
+                    LogAction::Rename
+                } else {
+                    LogAction::Move
+                };
+                PendingLog::new(group_code, asset_type, new_rel_path, action).detail(format!("原路径: {}", old_rel_path))
+            }
+            SyncOperation::RenameDirectory { old_dir, new_dir, .. } => {
+                PendingLog::new(group_code, asset_type, &format!("{}/", new_dir), LogAction::Move)
+                    .detail(format!("原目录: {}/", old_dir))
+            }
+        }
+    }
+
+    fn is_upload_of(&self, scope: (&str, &str), path: &str) -> bool {
+        matches!(self, SyncOperation::Upload { rel_path, .. } if self.scope() == scope && rel_path == path)
+    }
+
+    fn is_rename_to(&self, scope: (&str, &str), path: &str) -> bool {
+        matches!(self, SyncOperation::Rename { new_rel_path, .. } if self.scope() == scope && new_rel_path == path)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedOperation {
+    pub id: u64,
+    pub operation: SyncOperation,
+    pub created_at: i64,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueFile {
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    pending: Vec<QueuedOperation>,
+    #[serde(default)]
+    failed: Vec<QueuedOperation>,
+}
+
+impl QueueFile {
+    fn append(&mut self, operation: SyncOperation) {
+        self.next_id += 1;
+        self.pending.push(QueuedOperation {
+            id: self.next_id,
+            operation,
+            created_at: chrono::Utc::now().timestamp(),
+            attempts: 0,
+            last_error: None,
+        });
+    }
+
+    fn take(&mut self, pred: impl Fn(&SyncOperation) -> bool) -> Option<SyncOperation> {
+        let pos = self.pending.iter().position(|q| pred(&q.operation))?;
+        Some(self.pending.remove(pos).operation)
+    }
+
+    /// 入队并合并被后续本地变更取代的操作
+    fn push(&mut self, operation: SyncOperation) {
+        let (group_code, asset_type) = {
+            let (g, a) = operation.scope();
+            (g.to_string(), a.to_string())
+        };
+        let scope = (group_code.as_str(), asset_type.as_str());
+
+        match &operation {
+            SyncOperation::Upload { rel_path, .. } => {
+                // 只保留最后一次上传
+                self.take(|op| op.is_upload_of(scope, rel_path));
+                self.append(operation.clone());
+            }
+            SyncOperation::Delete { rel_path, .. } => {
+                // 尚未上传的内容无需再传；重命名后删除等同于删除原文件
+                self.take(|op| op.is_upload_of(scope, rel_path));
+                let target = match self.take(|op| op.is_rename_to(scope, rel_path)) {
+                    Some(SyncOperation::Rename { old_rel_path, .. }) => old_rel_path,
+                    _ => rel_path.clone(),
+                };
+                self.append(SyncOperation::Delete {
+                    group_code,
+                    asset_type,
+                    rel_path: target,
+                });
+            }
+            SyncOperation::Rename { old_rel_path, new_rel_path, .. } => {
+                let had_upload = self.take(|op| op.is_upload_of(scope, old_rel_path)).is_some();
+                // 连续重命名合并为一次
+                let from = match self.take(|op| op.is_rename_to(scope, old_rel_path)) {
+                    Some(SyncOperation::Rename { old_rel_path, .. }) => old_rel_path,
+                    _ => old_rel_path.clone(),
+                };
+                if &from != new_rel_path {
+                    self.append(SyncOperation::Rename {
+                        group_code: group_code.clone(),
+                        asset_type: asset_type.clone(),
+                        old_rel_path: from,
+                        new_rel_path: new_rel_path.clone(),
+                    });
+                }
+                if had_upload {
+                    self.append(SyncOperation::Upload {
+                        group_code,
+                        asset_type,
+                        rel_path: new_rel_path.clone(),
+                    });
+                }
+            }
+            SyncOperation::RenameDirectory { .. } => self.append(operation.clone()),
+        }
+    }
+}
+
+/// 离线队列状态
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueueStatus {
+    pub online: bool,
+    pub pending: Vec<QueuedOperation>,
+    pub failed: Vec<QueuedOperation>,
+}
+
+struct QueueState {
+    root: Option<PathBuf>,
+    file: QueueFile,
+    online: bool,
+}
+
+lazy_static::lazy_static! {
+    static ref STATE: Mutex<QueueState> = Mutex::new(QueueState {
+        root: None,
+        file: QueueFile::default(),
+        online: true,
+    });
+    static ref WAKE: Notify = Notify::new();
+}
+
+fn queue_path(root: &Path) -> PathBuf {
+    SyncIndex::meta_dir(root).join(QUEUE_FILE_NAME)
+}
+
+fn load(root: &Path) -> QueueFile {
+    fs::read_to_string(queue_path(root))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save(root: &Path, file: &QueueFile) -> Result<(), String> {
+    let path = queue_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string(file).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("写入离线队列失败: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("保存离线队列失败: {}", e))
+}
+
+/// 访问队列（保存在优先级最高的根目录，根目录变化时重新加载）
+fn locked<T>(f: impl FnOnce(&mut QueueState, &Path) -> Result<T, String>) -> Result<T, String> {
+    sync_engine::config().ok_or("同步引擎未配置")?;
+    let root = sync_roots::all()
+        .into_iter()
+        .next()
+        .map(|r| PathBuf::from(r.path))
+        .ok_or("未配置同步根目录")?;
+
+    let mut state = STATE.lock().map_err(|e| e.to_string())?;
+    if state.root.as_deref() != Some(root.as_path()) {
+        state.file = load(&root);
+        state.root = Some(root.clone());
+    }
+    f(&mut state, &root)
+}
+
+/// 修改队列并持久化
+fn with_queue<T>(f: impl FnOnce(&mut QueueFile) -> T) -> Result<T, String> {
+    locked(|state, root| {
+        let result = f(&mut state.file);
+        save(root, &state.file)?;
+        Ok(result)
+    })
+}
+
+fn status() -> QueueStatus {
+    locked(|state, _| {
+        Ok(QueueStatus {
+            online: state.online,
+            pending: state.file.pending.clone(),
+            failed: state.file.failed.clone(),
+        })
+    })
+    .unwrap_or_default()
+}
+
+fn set_online(app: &AppHandle, online: bool) {
+    let changed = STATE
+        .lock()
+        .map(|mut s| std::mem::replace(&mut s.online, online) != online)
+        .unwrap_or(false);
+    if changed {
+        log::info!("[SyncQueue] 服务器{}", if online { "已连接" } else { "不可达，操作将排队等待" });
+        let _ = app.emit("sync-connectivity", online);
+    }
+}
+
+/// 队列中待处理的路径集合
+#[derive(Debug, Default)]
+pub struct PendingPaths(HashSet<String>);
+
+impl PendingPaths {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, rel_path: &str) -> bool {
+        self.0.contains(rel_path)
+            || self
+                .0
+                .iter()
+                .any(|p| p.ends_with('/') && rel_path.starts_with(p.as_str()))
+    }
+}
+
+/// 某个资源文件夹下有待发送操作的相对路径（同步时跳过，避免与队列重复处理）
+pub fn pending_paths(group_code: &str, asset_type: &str) -> PendingPaths {
+    locked(|state, _| {
+        Ok(PendingPaths(
+            state
+                .file
+                .pending
+                .iter()
+                .filter(|q| q.operation.scope() == (group_code, asset_type))
+                .flat_map(|q| q.operation.paths())
+                .collect(),
+        ))
+    })
+    .unwrap_or_default()
+}
+
+/// 加入队列（不立即发送，调用 wake 触发）
+pub fn enqueue(operation: SyncOperation) -> Result<(), String> {
+    with_queue(|q| q.push(operation))
+}
+
+/// 唤醒后台任务尽快发送队列
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// 项目所在根目录及资源文件夹的本地路径
+fn locate(group_code: &str, asset_type: &str) -> Option<(SyncRoot, PathBuf)> {
+    let location = sync_roots::locate(group_code)?;
+    let template = folder_template::current().find(asset_type)?.clone();
+    Some((location.root, Path::new(&location.group.path).join(template.name)))
+}
+
+/// 资源移动后的 storage_key：把末尾的原相对路径替换为新路径
+fn moved_storage_key(storage_key: &str, old_rel_path: &str, new_rel_path: &str) -> Result<String, String> {
+    let base = storage_key
+        .strip_suffix(old_rel_path)
+        .filter(|base| base.is_empty() || base.ends_with('/'))
+        .ok_or_else(|| format!("无法确定 {} 在服务端的新位置", new_rel_path))?;
+    Ok(format!("{}{}", base, new_rel_path))
+}
+
+/// 执行一个操作；各根目录的索引按需加载到 indexes 中
+async fn execute(
+    client: &SyncClient,
+    indexes: &mut HashMap<PathBuf, SyncIndex>,
+    operation: &SyncOperation,
+) -> Result<(), String> {
+    let (group_code, asset_type) = operation.scope();
+    let (root, asset_dir) = match locate(group_code, asset_type) {
+        Some(found) => found,
+        None => {
+            log::warn!("[SyncQueue] 项目 {} 已不存在，丢弃操作", group_code);
+            return Ok(());
+        }
+    };
+    let client = client.clone().with_limits(&root);
+    let client = &client;
+    let root_path = PathBuf::from(&root.path);
+    let index = match indexes.entry(root_path) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => {
+            let index = SyncIndex::load(e.key())?;
+            e.insert(index)
+        }
+    };
+    let storage_key = |index: &SyncIndex, rel_path: &str| {
+        index
+            .get(&SyncIndex::key(group_code, asset_type, rel_path))
+            .and_then(|e| e.storage_key.clone())
+    };
+
+    match operation {
+        SyncOperation::Upload { rel_path, .. } => {
+            let path = asset_dir.join(rel_path);
+            if !path.is_file() {
+                return Ok(());
+            }
+            let (manifest, part_size) =
+                delta_sync::upload_file(client, group_code, asset_type, rel_path, &path).await?;
+            let mut entry = sync_engine::index_entry_with_hash(&path, None, manifest)?;
+            entry.part_size = part_size;
+            index.insert(SyncIndex::key(group_code, asset_type, rel_path), entry);
+        }
+        SyncOperation::Rename { old_rel_path, new_rel_path, .. } => {
+            // 索引中没有服务端位置时按原路径在服务端查找；两边都不存在才交给下次同步直接上传
+            let key = match storage_key(index, old_rel_path) {
+                Some(k) => k,
+                None => {
+                    let remote = client.list_resources(group_code, asset_type).await?;
+                    match remote.into_iter().find(|r| normalize_rel_path(&r.rel_path) == *old_rel_path) {
+                        Some(r) => r.storage_key,
+                        None => {
+                            log::warn!("[SyncQueue] 服务端不存在 {}，重命名改为由下次同步上传", old_rel_path);
+                            return Ok(());
+                        }
+                    }
+                }
+            };
+            let old_key = SyncIndex::key(group_code, asset_type, old_rel_path);
+            let new_key = SyncIndex::key(group_code, asset_type, new_rel_path);
+            let new_storage_key = moved_storage_key(&key, old_rel_path, new_rel_path)?;
+
+            // 服务端移动成功后才更新索引；内容若有变化由下次同步按新路径上传
+            let new_storage_key = client.move_resource(group_code, &key, &new_storage_key).await?;
+            let mut entry = index.remove(&old_key).unwrap_or_default();
+            entry.storage_key = Some(new_storage_key);
+            entry.remote_modified_at = None;
+            index.insert(new_key, entry);
+        }
+        SyncOperation::RenameDirectory { old_dir, new_dir, .. } => {
+            // 逐个文件在服务端移动，已完成的文件会从索引中移走，重试时只处理剩余部分
+            let prefix = format!("{}/", old_dir);
+            let entries: Vec<(String, crate::sync_index::IndexEntry)> = index
+                .entries_under(group_code, asset_type)
+                .into_iter()
+                .filter(|(rel_path, _)| rel_path.starts_with(&prefix))
+                .collect();
+
+            for (old_rel_path, mut entry) in entries {
+                let new_rel_path = format!("{}/{}", new_dir, &old_rel_path[prefix.len()..]);
+                let old_key = SyncIndex::key(group_code, asset_type, &old_rel_path);
+                let new_key = SyncIndex::key(group_code, asset_type, &new_rel_path);
+
+                if let Some(key) = entry.storage_key.take() {
+                    let new_storage_key = moved_storage_key(&key, &old_rel_path, &new_rel_path)?;
+                    entry.storage_key = Some(client.move_resource(group_code, &key, &new_storage_key).await?);
+                    entry.remote_modified_at = None;
+                }
+                index.remove(&old_key);
+                index.insert(new_key, entry);
+            }
+        }
+        SyncOperation::Delete { rel_path, .. } => {
+            let index_key = SyncIndex::key(group_code, asset_type, rel_path);
+            let key = match storage_key(index, rel_path) {
+                Some(k) => k,
+                None => {
+                    index.remove(&index_key);
+                    return Ok(());
+                }
+            };
+            // 文件已被恢复，不再删除
+            if asset_dir.join(rel_path).exists() {
+                return Ok(());
+            }
+            client.delete_resource(group_code, &key).await?;
+            index.remove(&index_key);
+        }
+    }
+    Ok(())
+}
+
+/// 服务器可达时按顺序发送队列中的操作
+async fn replay(app: &AppHandle) -> Result<(), String> {
+    let config = match sync_engine::config() {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+    let client = SyncClient::new(&config.server_url, config.token.clone());
+    if !client.is_reachable().await {
+        set_online(app, false);
+        return Ok(());
+    }
+    set_online(app, true);
+
+    // 同步进行中时下一轮再发送
+    let _run = match sync_engine::begin_run() {
+        Ok(guard) => guard,
+        Err(_) => return Ok(()),
+    };
+
+    let mut indexes = HashMap::new();
+
+    while let Some(queued) = locked(|state, _| Ok(state.file.pending.first().cloned()))? {
+        let pending_log = queued.operation.log();
+        let result = execute(&client, &mut indexes, &queued.operation).await;
+        pending_log.finish(0, &result);
+        match result {
+            Ok(()) => {
+                with_queue(|q| q.pending.retain(|p| p.id != queued.id))?;
+            }
+            Err(e) => {
+                if !client.is_reachable().await {
+                    set_online(app, false);
+                    break;
+                }
+                log::warn!("[SyncQueue] 操作失败 {:?}: {}", queued.operation, e);
+                let gave_up = with_queue(|q| {
+                    let pos = q.pending.iter().position(|p| p.id == queued.id)?;
+                    let op = &mut q.pending[pos];
+                    op.attempts += 1;
+                    op.last_error = Some(e.clone());
+                    if op.attempts >= MAX_ATTEMPTS {
+                        let op = q.pending.remove(pos);
+                        q.failed.push(op);
+                        return Some(true);
+                    }
+                    Some(false)
+                })?;
+                // 未放弃时停止本轮，保证后续操作不越过它执行
+                if gave_up != Some(true) {
+                    break;
+                }
+            }
+        }
+    }
+
+    for index in indexes.values() {
+        index.save()?;
+    }
+    let _ = app.emit("sync-queue", status());
+    Ok(())
+}
+
+/// 启动后台任务：队列非空时定期检查连通性并重放
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)) => {}
+                _ = WAKE.notified() => {}
+            }
+            let has_pending = locked(|state, _| Ok(!state.file.pending.is_empty())).unwrap_or(false);
+            if has_pending {
+                if let Err(e) = replay(&app).await {
+                    log::warn!("[SyncQueue] 重放队列失败: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// 将操作加入离线队列（服务器可达时会立即发送）
+#[tauri::command]
+pub fn enqueue_sync_operation(app: AppHandle, operation: SyncOperation) -> Result<(), String> {
+    enqueue(operation)?;
+    let _ = app.emit("sync-queue", status());
+    wake();
+    Ok(())
+}
+
+/// 获取离线队列状态
+#[tauri::command]
+pub fn get_sync_queue() -> QueueStatus {
+    status()
+}
+
+/// 将失败的操作重新加入队列
+#[tauri::command]
+pub fn retry_failed_operations(app: AppHandle) -> Result<u32, String> {
+    let count = with_queue(|q| {
+        let failed = std::mem::take(&mut q.failed);
+        let count = failed.len() as u32;
+        for mut op in failed {
+            op.attempts = 0;
+            op.last_error = None;
+            q.pending.push(op);
+        }
+        count
+    })?;
+    let _ = app.emit("sync-queue", status());
+    WAKE.notify_one();
+    Ok(count)
+}
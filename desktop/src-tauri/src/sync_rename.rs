@@ -0,0 +1,209 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use crate::commands::{self, LocalFile};
+use crate::file_sync::SyncRule;
+use crate::sync_index::IndexEntry;
+use crate::sync_planner::normalize_rel_path;
+use crate::sync_queue::{self, SyncOperation};
+
+/// 识别出的本地重命名/移动
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectedRename {
+    pub old_rel_path: String,
+    pub new_rel_path: String,
+}
+
+/// 文件系统标识：Unix 为 inode，Windows 为 NTFS 文件索引
+pub fn file_id(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(path).ok().map(|m| m.ino())
+    }
+
+    #[cfg(windows)]
+    {
+        let handle = winapi_util::Handle::from_path_any(path).ok()?;
+        winapi_util::file::information(&handle).ok().map(|info| info.file_index())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// 对比索引识别重命名：原路径消失、新路径出现，且文件标识相同或内容哈希相同
+///
+/// can_upload 按新路径判断是否允许把变更同步到服务端。
+fn detect(
+    asset_dir: &Path,
+    local: &[LocalFile],
+    base: &BTreeMap<String, IndexEntry>,
+    can_upload: &dyn Fn(&str) -> bool,
+) -> Vec<DetectedRename> {
+    let local_paths: HashSet<String> = local.iter().map(|f| normalize_rel_path(&f.rel_path)).collect();
+
+    // 服务端存在、本地已消失的条目
+    let mut disappeared: Vec<(&String, &IndexEntry)> = base
+        .iter()
+        .filter(|(rel_path, entry)| entry.storage_key.is_some() && !local_paths.contains(*rel_path))
+        .collect();
+    if disappeared.is_empty() {
+        return Vec::new();
+    }
+
+    let mut renames = Vec::new();
+    for file in local.iter().filter(|f| !f.is_dir && f.placeholder.is_none()) {
+        let rel_path = normalize_rel_path(&file.rel_path);
+        if base.contains_key(&rel_path) || !can_upload(&rel_path) {
+            continue;
+        }
+        let candidates: Vec<usize> = disappeared
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, entry))| entry.size == file.size)
+            .map(|(i, _)| i)
+            .collect();
+        if candidates.is_empty() {
+            continue;
+        }
+
+        let path = asset_dir.join(&rel_path);
+        let id = file_id(&path);
+        let mut matched = candidates
+            .iter()
+            .copied()
+            .find(|&i| id.is_some() && disappeared[i].1.file_id == id);
+
+        if matched.is_none() && candidates.iter().any(|&i| disappeared[i].1.hash.is_some()) {
+            if let Ok(hash) = commands::sha256_file(&path) {
+                matched = candidates
+                    .iter()
+                    .copied()
+                    .find(|&i| disappeared[i].1.hash.as_deref() == Some(hash.as_str()));
+            }
+        }
+
+        if let Some(i) = matched {
+            let (old_rel_path, _) = disappeared.remove(i);
+            renames.push(DetectedRename {
+                old_rel_path: old_rel_path.clone(),
+                new_rel_path: rel_path,
+            });
+            if disappeared.is_empty() {
+                break;
+            }
+        }
+    }
+    renames
+}
+
+fn parent_dir(rel_path: &str) -> &str {
+    rel_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// 去掉相同的尾部路径后剩下的目录部分，如 `渲染/a.png → 渲染图/a.png` 得到 (`渲染`, `渲染图`)
+fn renamed_dirs<'a>(old: &'a str, new: &'a str) -> Option<(&'a str, &'a str)> {
+    let old_parts: Vec<&str> = old.split('/').collect();
+    let new_parts: Vec<&str> = new.split('/').collect();
+    let common = old_parts
+        .iter()
+        .rev()
+        .zip(new_parts.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 || common == old_parts.len() || common == new_parts.len() {
+        return None;
+    }
+
+    let old_len: usize = old_parts[..old_parts.len() - common].iter().map(|p| p.len() + 1).sum::<usize>() - 1;
+    let new_len: usize = new_parts[..new_parts.len() - common].iter().map(|p| p.len() + 1).sum::<usize>() - 1;
+    Some((&old[..old_len], &new[..new_len]))
+}
+
+/// 识别资源文件夹内的重命名，确认未超出批量保护阈值后再调用 enqueue 发送到服务端
+///
+/// 只处理双向同步的文件；已在队列中的路径跳过。
+pub fn detect_renames(
+    group_code: &str,
+    asset_type: &str,
+    asset_dir: &Path,
+    local: &[LocalFile],
+    base: &BTreeMap<String, IndexEntry>,
+    rule_for: &dyn Fn(&str) -> SyncRule,
+) -> Vec<DetectedRename> {
+    let pending = sync_queue::pending_paths(group_code, asset_type);
+    let local: Vec<LocalFile> = local
+        .iter()
+        .filter(|f| !pending.contains(&normalize_rel_path(&f.rel_path)))
+        .cloned()
+        .collect();
+    let base: BTreeMap<String, IndexEntry> = base
+        .iter()
+        .filter(|(rel_path, _)| !pending.contains(rel_path))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    detect(asset_dir, &local, &base, &|rel_path| rule_for(rel_path) == SyncRule::Bidirectional)
+}
+
+/// 将识别结果加入离线队列：整个目录被重命名时合并为一次批量操作，返回加入队列的操作数
+pub fn enqueue(
+    group_code: &str,
+    asset_type: &str,
+    renames: Vec<DetectedRename>,
+    base: &BTreeMap<String, IndexEntry>,
+) -> Result<usize, String> {
+    let mut by_dir: HashMap<(String, String), Vec<DetectedRename>> = HashMap::new();
+    let mut single = Vec::new();
+    for rename in renames {
+        match renamed_dirs(&rename.old_rel_path, &rename.new_rel_path) {
+            Some((old_dir, new_dir)) => by_dir
+                .entry((old_dir.to_string(), new_dir.to_string()))
+                .or_default()
+                .push(rename),
+            None => single.push(rename),
+        }
+    }
+
+    let mut operations = Vec::new();
+    for ((old_dir, new_dir), files) in by_dir {
+        let prefix = format!("{}/", old_dir);
+        let total = base.keys().filter(|k| k.starts_with(&prefix)).count();
+        if files.len() > 1 && files.len() == total {
+            log::info!("[SyncRename] 目录重命名 {} -> {}（{} 个文件）", old_dir, new_dir, files.len());
+            operations.push(SyncOperation::RenameDirectory {
+                group_code: group_code.to_string(),
+                asset_type: asset_type.to_string(),
+                old_dir,
+                new_dir,
+            });
+        } else {
+            single.extend(files);
+        }
+    }
+
+    for rename in single {
+        let kind = if parent_dir(&rename.old_rel_path) == parent_dir(&rename.new_rel_path) {
+            "重命名"
+        } else {
+            "移动"
+        };
+        log::info!("[SyncRename] 文件{} {} -> {}", kind, rename.old_rel_path, rename.new_rel_path);
+        operations.push(SyncOperation::Rename {
+            group_code: group_code.to_string(),
+            asset_type: asset_type.to_string(),
+            old_rel_path: rename.old_rel_path,
+            new_rel_path: rename.new_rel_path,
+        });
+    }
+
+    let count = operations.len();
+    for operation in operations {
+        sync_queue::enqueue(operation)?;
+    }
+    Ok(count)
+}
@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use crate::commands::GroupFolder;
+use crate::scanner;
+use crate::sync_engine;
+
+const ROOTS_FILE_NAME: &str = "sync_roots.json";
+
+/// 同步根目录及其设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRoot {
+    pub path: String,
+    /// 是否参与自动同步；停用的根目录仍可打开和查找项目
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 数值大的先同步；同一项目出现在多个根目录时以优先级高的为准
+    #[serde(default)]
+    pub priority: i32,
+    /// 上传限速（KB/s），为空不限速
+    #[serde(default)]
+    pub upload_limit_kbps: Option<u64>,
+    /// 下载限速（KB/s），为空不限速
+    #[serde(default)]
+    pub download_limit_kbps: Option<u64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl SyncRoot {
+    fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            enabled: true,
+            priority: 0,
+            upload_limit_kbps: None,
+            download_limit_kbps: None,
+        }
+    }
+}
+
+/// 项目所在位置
+#[derive(Debug, Clone)]
+pub struct GroupLocation {
+    pub root: SyncRoot,
+    pub group: GroupFolder,
+}
+
+/// 出现在多个位置的项目
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub group_code: String,
+    /// 按根目录优先级排列，第一个为生效的位置
+    pub paths: Vec<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref ROOTS: RwLock<Vec<SyncRoot>> = RwLock::new(Vec::new());
+    static ref ROOTS_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// 从配置目录加载根目录列表
+pub fn init(config_dir: PathBuf) {
+    let path = config_dir.join(ROOTS_FILE_NAME);
+    if let Ok(mut p) = ROOTS_PATH.write() {
+        *p = Some(path.clone());
+    }
+    if !path.exists() {
+        return;
+    }
+
+    let loaded = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str::<Vec<SyncRoot>>(&c).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(roots) => {
+            if let Ok(mut r) = ROOTS.write() {
+                *r = roots;
+            }
+        }
+        Err(e) => log::warn!("[SyncRoots] 加载根目录设置失败: {}", e),
+    }
+}
+
+/// 全部根目录，按优先级从高到低排列
+///
+/// 同步引擎配置的主根目录始终包含在内，未单独设置时按默认设置处理。
+pub fn all() -> Vec<SyncRoot> {
+    let mut roots = ROOTS.read().map(|r| r.clone()).unwrap_or_default();
+    if let Some(config) = sync_engine::config() {
+        let primary = Path::new(&config.root_dir);
+        if !config.root_dir.is_empty() && !roots.iter().any(|r| Path::new(&r.path) == primary) {
+            roots.push(SyncRoot::new(&config.root_dir));
+        }
+    }
+    roots.sort_by_key(|r| std::cmp::Reverse(r.priority));
+    roots
+}
+
+/// 参与同步的根目录
+pub fn enabled() -> Vec<SyncRoot> {
+    all().into_iter().filter(|r| r.enabled).collect()
+}
+
+/// 包含该路径的根目录
+pub fn root_of(path: &Path) -> Option<SyncRoot> {
+    all().into_iter().find(|r| path.starts_with(&r.path))
+}
+
+/// 在全部根目录中查找项目，重复时返回优先级高的位置
+pub fn locate(group_code: &str) -> Option<GroupLocation> {
+    all().into_iter().find_map(|root| {
+        let group = scanner::scan_groups(Path::new(&root.path))
+            .ok()?
+            .into_iter()
+            .find(|g| g.group_code == group_code)?;
+        Some(GroupLocation { root, group })
+    })
+}
+
+/// 查找出现在多个位置的项目
+pub fn duplicates() -> Vec<DuplicateGroup> {
+    let mut seen: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for root in all() {
+        let groups = match scanner::scan_groups(Path::new(&root.path)) {
+            Ok(g) => g,
+            Err(_) => continue,
+        };
+        for group in groups {
+            seen.entry(group.group_code).or_default().push(group.path);
+        }
+    }
+    seen.into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(group_code, paths)| DuplicateGroup { group_code, paths })
+        .collect()
+}
+
+fn validate(roots: &[SyncRoot]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for root in roots {
+        let path = Path::new(&root.path);
+        if root.path.trim().is_empty() || !path.is_dir() {
+            return Err(format!("根目录不存在: {}", root.path));
+        }
+        if !seen.insert(path.to_path_buf()) {
+            return Err(format!("根目录重复: {}", root.path));
+        }
+    }
+    for a in roots {
+        for b in roots {
+            if a.path != b.path && Path::new(&a.path).starts_with(&b.path) {
+                return Err(format!("根目录不能相互嵌套: {} 位于 {} 内", a.path, b.path));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 获取根目录列表
+#[tauri::command]
+pub fn get_sync_roots() -> Vec<SyncRoot> {
+    all()
+}
+
+/// 保存根目录列表，返回跨根目录重复的项目供界面提示
+#[tauri::command]
+pub async fn save_sync_roots(roots: Vec<SyncRoot>) -> Result<Vec<DuplicateGroup>, String> {
+    tokio::task::spawn_blocking(move || save_blocking(roots))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn save_blocking(roots: Vec<SyncRoot>) -> Result<Vec<DuplicateGroup>, String> {
+    validate(&roots)?;
+
+    let path = ROOTS_PATH
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("设置路径未初始化")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&roots).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("保存设置失败: {}", e))?;
+
+    *ROOTS.write().map_err(|e| e.to_string())? = roots;
+    Ok(duplicates())
+}
+
+/// 查找跨根目录重复的项目
+#[tauri::command]
+pub async fn find_duplicate_projects() -> Result<Vec<DuplicateGroup>, String> {
+    tokio::task::spawn_blocking(duplicates)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按项目编号查找本地项目文件夹
+#[tauri::command]
+pub async fn locate_project(group_code: String) -> Result<Option<GroupFolder>, String> {
+    tokio::task::spawn_blocking(move || locate(&group_code).map(|l| l.group))
+        .await
+        .map_err(|e| e.to_string())
+}
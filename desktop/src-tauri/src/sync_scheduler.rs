@@ -0,0 +1,242 @@
+use chrono::{Datelike, Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+use crate::sync_engine;
+
+/// 允许自动同步的时间段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// 开始时间 HH:MM
+    pub start: String,
+    /// 结束时间 HH:MM，早于开始时间表示跨零点
+    pub end: String,
+    /// 生效的星期（1=周一 … 7=周日），为空表示每天
+    #[serde(default)]
+    pub days: Vec<u32>,
+}
+
+impl TimeWindow {
+    fn parse_time(value: &str) -> Result<NaiveTime, String> {
+        NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| format!("无效的时间: {}", value))
+    }
+
+    fn contains(&self, now: chrono::DateTime<Local>) -> bool {
+        let (start, end) = match (Self::parse_time(&self.start), Self::parse_time(&self.end)) {
+            (Ok(s), Ok(e)) => (s, e),
+            _ => return false,
+        };
+        let time = now.time();
+        let (in_time, day) = if start <= end {
+            (time >= start && time < end, now.weekday())
+        } else if time >= start {
+            (true, now.weekday())
+        } else {
+            // 跨零点的后半段属于前一天的时间段
+            (time < end, now.weekday().pred())
+        };
+        in_time && (self.days.is_empty() || self.days.contains(&day.number_from_monday()))
+    }
+}
+
+/// 定时同步配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// 同步间隔（秒）
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    /// 允许同步的时间段，为空表示全天
+    #[serde(default)]
+    pub windows: Vec<TimeWindow>,
+    /// 连续失败后的最大重试间隔（秒）
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff_secs: u64,
+}
+
+fn default_interval() -> u64 {
+    1800
+}
+
+fn default_max_backoff() -> u64 {
+    3600 * 4
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_interval(),
+            windows: Vec::new(),
+            max_backoff_secs: default_max_backoff(),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.interval_secs < 60 {
+            return Err("同步间隔不能小于 1 分钟".to_string());
+        }
+        for window in &self.windows {
+            TimeWindow::parse_time(&window.start)?;
+            TimeWindow::parse_time(&window.end)?;
+            if window.days.iter().any(|d| !(1..=7).contains(d)) {
+                return Err("星期取值应为 1-7".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn in_window(&self, now: chrono::DateTime<Local>) -> bool {
+        self.windows.is_empty() || self.windows.iter().any(|w| w.contains(now))
+    }
+
+    /// 连续失败时按 2 的幂次延长间隔
+    fn delay(&self, failures: u32) -> Duration {
+        let secs = self
+            .interval_secs
+            .saturating_mul(1u64 << failures.min(16))
+            .min(self.max_backoff_secs.max(self.interval_secs));
+        Duration::from_secs(secs)
+    }
+}
+
+/// 定时同步状态
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchedulerStatus {
+    pub running: bool,
+    pub config: Option<SchedulerConfig>,
+    pub next_run_at: Option<String>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref STATUS: Mutex<SchedulerStatus> = Mutex::new(SchedulerStatus::default());
+    static ref TRIGGER: Notify = Notify::new();
+}
+
+/// 当前调度循环的代数，启动/停止时递增使旧循环退出
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn update(app: &AppHandle, f: impl FnOnce(&mut SchedulerStatus)) {
+    if let Ok(mut status) = STATUS.lock() {
+        f(&mut status);
+        let _ = app.emit("sync-scheduler", status.clone());
+    }
+}
+
+async fn run_loop(app: AppHandle, config: SchedulerConfig, generation: u64) {
+    let mut failures: u32 = 0;
+    // 启动（开启自动同步、登录或修改设置）后立即同步一次，与手动触发一样不受时间段限制
+    let mut first = true;
+    log::info!("[SyncScheduler] 启动定时同步，间隔 {} 秒", config.interval_secs);
+
+    while GENERATION.load(Ordering::SeqCst) == generation {
+        let triggered = if std::mem::take(&mut first) {
+            true
+        } else {
+            let delay = config.delay(failures);
+            let next = Local::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+            update(&app, |s| s.next_run_at = Some(next.format("%Y-%m-%d %H:%M:%S").to_string()));
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => false,
+                _ = TRIGGER.notified() => true,
+            }
+        };
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            break;
+        }
+        if !triggered && !config.in_window(Local::now()) {
+            log::info!("[SyncScheduler] 不在同步时间段内，跳过");
+            continue;
+        }
+
+        match sync_engine::run_once(&app).await {
+            Ok(summary) => {
+                failures = 0;
+                update(&app, |s| {
+                    s.consecutive_failures = 0;
+                    s.last_error = None;
+                });
+                let _ = app.emit("sync-completed", summary);
+            }
+            Err(e) if e == sync_engine::ALREADY_RUNNING => {
+                log::info!("[SyncScheduler] 同步正在进行中，跳过本次");
+            }
+            Err(e) => {
+                failures = failures.saturating_add(1);
+                log::warn!("[SyncScheduler] 同步失败（连续 {} 次）: {}", failures, e);
+                update(&app, |s| {
+                    s.consecutive_failures = failures;
+                    s.last_error = Some(e);
+                });
+            }
+        }
+    }
+
+    log::info!("[SyncScheduler] 定时同步已停止");
+}
+
+/// 启动后台定时同步（已运行时按新配置重启）
+#[tauri::command]
+pub fn start_sync_scheduler(app: AppHandle, config: Option<SchedulerConfig>) -> Result<(), String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    sync_engine::config().ok_or("同步引擎未配置")?;
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    // 唤醒旧循环使其退出
+    TRIGGER.notify_waiters();
+
+    update(&app, |s| {
+        s.running = true;
+        s.config = Some(config.clone());
+        s.consecutive_failures = 0;
+        s.last_error = None;
+    });
+    tauri::async_runtime::spawn(run_loop(app, config, generation));
+    Ok(())
+}
+
+/// 停止后台定时同步（进行中的同步会完成后退出）
+#[tauri::command]
+pub fn stop_sync_scheduler(app: AppHandle) -> Result<(), String> {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    TRIGGER.notify_waiters();
+    update(&app, |s| {
+        s.running = false;
+        s.next_run_at = None;
+    });
+    Ok(())
+}
+
+/// 立即同步：定时同步运行中时提前触发，否则在后台执行一次
+#[tauri::command]
+pub fn trigger_sync_now(app: AppHandle) -> Result<(), String> {
+    let running = STATUS.lock().map(|s| s.running).unwrap_or(false);
+    if running {
+        TRIGGER.notify_one();
+        return Ok(());
+    }
+
+    sync_engine::config().ok_or("同步引擎未配置")?;
+    tauri::async_runtime::spawn(async move {
+        match sync_engine::run_once(&app).await {
+            Ok(summary) => {
+                let _ = app.emit("sync-completed", summary);
+            }
+            Err(e) => log::warn!("[SyncScheduler] 同步失败: {}", e),
+        }
+    });
+    Ok(())
+}
+
+/// 获取定时同步状态
+#[tauri::command]
+pub fn get_sync_scheduler_status() -> SchedulerStatus {
+    STATUS.lock().map(|s| s.clone()).unwrap_or_default()
+}
@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// 回收站目录（位于根目录下，以 . 开头，扫描时自动跳过）
+pub const TRASH_DIR_NAME: &str = ".trash";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const FILES_DIR_NAME: &str = "files";
+
+/// 移入回收站的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashReason {
+    /// 服务端已删除
+    RemoteDeleted,
+    /// 被服务端新版本覆盖
+    RemoteOverwritten,
+    /// 处理冲突时被替换
+    ConflictReplaced,
+    /// 合并项目文件夹时与保留的文件内容相同
+    MergeDuplicate,
+}
+
+impl TrashReason {
+    pub fn description(&self) -> &'static str {
+        match self {
+            TrashReason::RemoteDeleted => "服务端已删除",
+            TrashReason::RemoteOverwritten => "被服务端新版本覆盖",
+            TrashReason::ConflictReplaced => "处理冲突时被替换",
+            TrashReason::MergeDuplicate => "合并项目文件夹时重复",
+        }
+    }
+}
+
+/// 回收站条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// 相对根目录的原始路径
+    pub original_path: String,
+    pub size: u64,
+    pub deleted_at: i64,
+    pub reason: TrashReason,
+}
+
+/// 回收站自动清理策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashPolicy {
+    /// 超过天数自动清理，0 表示不限
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u32,
+    /// 总大小上限（MB），超出时从最旧的开始清理，0 表示不限
+    #[serde(default = "default_max_size_mb")]
+    pub max_size_mb: u64,
+}
+
+fn default_max_age_days() -> u32 {
+    30
+}
+
+fn default_max_size_mb() -> u64 {
+    10240
+}
+
+impl Default for TrashPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: default_max_age_days(),
+            max_size_mb: default_max_size_mb(),
+        }
+    }
+}
+
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+lazy_static::lazy_static! {
+    /// 串行化回收站清单的读-改-写，避免并发移入和清理时丢失条目
+    static ref MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+}
+
+fn lock_manifest() -> MutexGuard<'static, ()> {
+    MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn trash_dir(root: &Path) -> PathBuf {
+    root.join(TRASH_DIR_NAME)
+}
+
+fn entry_dir(root: &Path, id: &str) -> PathBuf {
+    trash_dir(root).join(FILES_DIR_NAME).join(id)
+}
+
+fn load_manifest(root: &Path) -> Result<Vec<TrashEntry>, String> {
+    let path = trash_dir(root).join(MANIFEST_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取回收站失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析回收站失败: {}", e))
+}
+
+fn save_manifest(root: &Path, entries: &[TrashEntry]) -> Result<(), String> {
+    let dir = trash_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let content = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    let tmp = dir.join(format!("{}.tmp", MANIFEST_FILE_NAME));
+    fs::write(&tmp, content).map_err(|e| format!("写入回收站失败: {}", e))?;
+    fs::rename(&tmp, dir.join(MANIFEST_FILE_NAME)).map_err(|e| format!("保存回收站失败: {}", e))
+}
+
+/// 移动文件，跨卷时退化为复制后删除
+pub fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| format!("复制文件失败: {}", e))?;
+    fs::remove_file(from).map_err(|e| format!("删除文件失败: {}", e))
+}
+
+/// 将同步即将删除或覆盖的本地文件移入根目录回收站
+///
+/// 清单无法读取时不移动文件；写入清单失败时将文件移回原位置。
+pub fn move_to_trash(root: &Path, path: &Path, reason: TrashReason) -> Result<TrashEntry, String> {
+    let file_name = path.file_name().ok_or("无效的文件路径")?;
+    let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let now = chrono::Local::now();
+    let id = format!(
+        "{}-{:04}",
+        now.format("%Y%m%d%H%M%S%3f"),
+        SEQUENCE.fetch_add(1, Ordering::SeqCst) % 10000
+    );
+    let original_path = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let entry = TrashEntry {
+        id,
+        original_path,
+        size,
+        deleted_at: now.timestamp(),
+        reason,
+    };
+
+    let _lock = lock_manifest();
+    let mut entries = load_manifest(root)?;
+    let stored = entry_dir(root, &entry.id).join(file_name);
+    move_file(path, &stored)?;
+
+    entries.push(entry.clone());
+    if let Err(e) = save_manifest(root, &entries) {
+        match move_file(&stored, path) {
+            Ok(()) => remove_entry_files(root, &entry.id),
+            Err(rollback) => log::error!("[SyncTrash] 回收站清单写入失败且无法移回 {:?}: {}", path, rollback),
+        }
+        return Err(e);
+    }
+
+    log::info!("[SyncTrash] {} -> 回收站 ({})", entry.original_path, reason.description());
+    Ok(entry)
+}
+
+fn remove_entry_files(root: &Path, id: &str) {
+    let dir = entry_dir(root, id);
+    if dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            log::warn!("[SyncTrash] 清理 {:?} 失败: {}", dir, e);
+        }
+    }
+}
+
+/// 按策略清理过期或超出容量的条目，返回清理数量
+pub fn auto_purge(root: &Path, policy: &TrashPolicy) -> Result<u32, String> {
+    let _lock = lock_manifest();
+    let mut entries = load_manifest(root)?;
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    entries.sort_by_key(|e| e.deleted_at);
+
+    let now = chrono::Utc::now().timestamp();
+    let max_age = policy.max_age_days as i64 * 86400;
+    let max_size = policy.max_size_mb * 1024 * 1024;
+    let mut total: u64 = entries.iter().map(|e| e.size).sum();
+
+    let mut kept = Vec::new();
+    let mut purged = Vec::new();
+    for entry in entries {
+        let expired = policy.max_age_days > 0 && now - entry.deleted_at > max_age;
+        let oversize = policy.max_size_mb > 0 && total > max_size;
+        if expired || oversize {
+            total = total.saturating_sub(entry.size);
+            purged.push(entry.id);
+        } else {
+            kept.push(entry);
+        }
+    }
+
+    // 先更新清单再删除文件，清单写入失败时文件仍可见
+    if !purged.is_empty() {
+        save_manifest(root, &kept)?;
+    }
+    for id in &purged {
+        remove_entry_files(root, id);
+    }
+    Ok(purged.len() as u32)
+}
+
+/// 列出回收站内容（最新的在前）
+#[tauri::command]
+pub fn list_trash(root_dir: String) -> Result<Vec<TrashEntry>, String> {
+    let mut entries = load_manifest(Path::new(&root_dir))?;
+    entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+    Ok(entries)
+}
+
+/// 从回收站恢复文件，原位置已有文件时恢复为带时间后缀的副本，返回恢复后的路径
+#[tauri::command]
+pub fn restore_from_trash(root_dir: String, id: String) -> Result<String, String> {
+    let root = Path::new(&root_dir);
+    let _lock = lock_manifest();
+    let mut entries = load_manifest(root)?;
+    let pos = entries.iter().position(|e| e.id == id).ok_or("回收站中不存在该文件")?;
+    let entry = entries[pos].clone();
+
+    let original = root.join(&entry.original_path);
+    let file_name = original.file_name().ok_or("无效的文件路径")?.to_os_string();
+    let stored = entry_dir(root, &entry.id).join(&file_name);
+    if !stored.exists() {
+        return Err("回收站文件已丢失".to_string());
+    }
+
+    let target = if original.exists() {
+        let stem = original.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let ext = original
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let suffix = chrono::Local::now().format("%Y%m%d%H%M%S");
+        original.with_file_name(format!("{}_恢复_{}{}", stem, suffix, ext))
+    } else {
+        original
+    };
+
+    move_file(&stored, &target)?;
+    entries.remove(pos);
+    if let Err(e) = save_manifest(root, &entries) {
+        let _ = move_file(&target, &stored);
+        return Err(e);
+    }
+    remove_entry_files(root, &entry.id);
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// 清空回收站；指定 ids 时只删除这些条目，返回删除数量
+#[tauri::command]
+pub fn purge_trash(root_dir: String, ids: Option<Vec<String>>) -> Result<u32, String> {
+    let root = Path::new(&root_dir);
+    let _lock = lock_manifest();
+    let entries = load_manifest(root)?;
+
+    let (purge, kept): (Vec<TrashEntry>, Vec<TrashEntry>) = entries
+        .into_iter()
+        .partition(|e| ids.as_ref().map(|ids| ids.contains(&e.id)).unwrap_or(true));
+
+    save_manifest(root, &kept)?;
+    for entry in &purge {
+        remove_entry_files(root, &entry.id);
+    }
+
+    Ok(purge.len() as u32)
+}
@@ -0,0 +1,228 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+use crate::file_versions;
+use crate::folder_template;
+use crate::project_archive;
+use crate::scanner;
+use crate::selective_sync;
+use crate::sync_engine::{self, LocalState};
+use crate::sync_guard::{self, ChangeCounts, ChangeKind};
+use crate::sync_ignore;
+use crate::sync_index::SyncIndex;
+use crate::sync_queue;
+use crate::sync_rename;
+use crate::sync_roots;
+
+/// 连续事件合并的静默时间
+const DEBOUNCE: Duration = Duration::from_secs(3);
+
+lazy_static::lazy_static! {
+    static ref WATCHER: Mutex<Option<RecommendedWatcher>> = Mutex::new(None);
+}
+
+/// 事件涉及的（项目目录名, 资源文件夹名）
+fn affected_folder(root: &Path, path: &Path) -> Option<(String, String)> {
+    let rel = path.strip_prefix(root).ok()?;
+    let mut parts = rel.components().filter_map(|c| match c {
+        Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+        _ => None,
+    });
+    let project = parts.next()?;
+    let folder = parts.next()?;
+    // 跳过 .sync、.trash 等元数据目录
+    if project.starts_with('.') || folder.starts_with('.') {
+        return None;
+    }
+    Some((project, folder))
+}
+
+/// 在受影响的资源文件夹内识别重命名，经过批量保护检查后交给离线队列
+fn handle_changes(app: &AppHandle, root: &Path, folders: HashSet<(String, String)>) {
+    let index = match SyncIndex::load(root) {
+        Ok(i) => i,
+        Err(e) => {
+            log::warn!("[SyncWatcher] {}", e);
+            return;
+        }
+    };
+    let templates = folder_template::current();
+    let settings = selective_sync::current();
+    let mut detected = Vec::new();
+    let mut counts: HashMap<String, ChangeCounts> = HashMap::new();
+
+    for (project, folder) in folders {
+        let project_path = root.join(&project);
+        let group = match scanner::parse_group_folder(&project, &project_path) {
+            Some(g) => g,
+            None => continue,
+        };
+        let template = match templates.folders.iter().find(|t| t.name == folder) {
+            Some(t) => t,
+            None => continue,
+        };
+        let asset_type = match &template.asset_type {
+            Some(t) => t,
+            None => continue,
+        };
+        if settings.mode_for(&group.group_code) == selective_sync::SyncMode::Excluded
+            || project_archive::is_archived(&project_path)
+        {
+            continue;
+        }
+
+        let asset_dir = project_path.join(&template.name);
+        match sync_engine::local_state(&asset_dir, &group.group_code, template, asset_type, &index, &settings) {
+            Ok(LocalState { local, base, .. }) => {
+                let rule_for = sync_engine::folder_rule(template);
+                let renames =
+                    sync_rename::detect_renames(&group.group_code, asset_type, &asset_dir, &local, &base, &rule_for);
+                if !renames.is_empty() {
+                    let entry = counts.entry(group.group_code.clone()).or_default();
+                    sync_engine::count_changes(entry, &template.name, &renames, &[]);
+                    detected.push((group.group_code, asset_type.clone(), renames, base));
+                }
+            }
+            Err(e) => log::warn!("[SyncWatcher] 处理 {}/{} 失败: {}", project, folder, e),
+        }
+    }
+    if detected.is_empty() {
+        return;
+    }
+
+    let guard = sync_engine::config().map(|c| c.guard).unwrap_or_default();
+    let blocked = sync_guard::evaluate(app, &guard, &counts);
+    let mut queued = 0;
+    for (group_code, asset_type, renames, base) in detected {
+        if blocked.contains(&group_code) {
+            continue;
+        }
+        let count = renames.len();
+        match sync_rename::enqueue(&group_code, &asset_type, renames, &base) {
+            Ok(n) => {
+                queued += n;
+                for _ in 0..count {
+                    sync_guard::record(&group_code, ChangeKind::Modification);
+                }
+            }
+            Err(e) => log::warn!("[SyncWatcher] 重命名加入队列失败: {}", e),
+        }
+    }
+
+    if queued > 0 {
+        sync_queue::wake();
+    }
+}
+
+/// 处理一批合并后的事件，跳过被忽略的路径
+fn handle_events(app: &AppHandle, roots: &[PathBuf], events: Vec<Event>) {
+    let relevant = |path: &Path| {
+        roots
+            .iter()
+            .find(|r| path.starts_with(r))
+            .filter(|r| affected_folder(r, path).is_some() && !sync_ignore::is_ignored_in(r, path))
+    };
+
+    // 内容变化的文件保存本地版本，等待写入完成可能较久，不阻塞重命名识别
+    let saved: Vec<PathBuf> = events
+        .iter()
+        .filter(|e| is_content_change(&e.kind))
+        .flat_map(|e| e.paths.iter())
+        .filter(|p| p.is_file() && relevant(p).is_some())
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if !saved.is_empty() {
+        tokio::task::spawn_blocking(move || file_versions::snapshot_when_stable(saved));
+    }
+
+    // 按所在根目录分组
+    let mut by_root: HashMap<PathBuf, HashSet<(String, String)>> = HashMap::new();
+    for path in events.iter().filter(|e| is_structural(&e.kind)).flat_map(|e| e.paths.iter()) {
+        if let Some(root) = relevant(path) {
+            if let Some(folder) = affected_folder(root, path) {
+                by_root.entry(root.clone()).or_default().insert(folder);
+            }
+        }
+    }
+    for (root, folders) in by_root {
+        handle_changes(app, &root, folders);
+    }
+}
+
+fn is_structural(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(notify::event::ModifyKind::Name(_))
+    )
+}
+
+/// 可能产生新内容的事件（编辑器常先写临时文件再重命名覆盖）
+fn is_content_change(kind: &EventKind) -> bool {
+    use notify::event::{ModifyKind, RenameMode};
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any)
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Both))
+    )
+}
+
+/// 启动各同步根目录的文件监听，识别重命名/移动并同步到服务端，并为保存的作品文件保留本地版本
+#[tauri::command]
+pub fn start_file_watcher(app: AppHandle) -> Result<(), String> {
+    sync_engine::config().ok_or("同步引擎未配置")?;
+    let roots: Vec<PathBuf> = sync_roots::enabled().into_iter().map(|r| PathBuf::from(r.path)).collect();
+    if roots.is_empty() {
+        return Err("未配置同步根目录".to_string());
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            if is_structural(&event.kind) || is_content_change(&event.kind) {
+                let _ = tx.send(event);
+            }
+        }
+    })
+    .map_err(|e| format!("创建文件监听失败: {}", e))?;
+    for root in &roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(|e| format!("监听目录 {:?} 失败: {}", root, e))?;
+    }
+    log::info!("[SyncWatcher] 开始监听 {:?}", roots);
+
+    // 替换旧的监听器，旧通道关闭后其处理任务自动退出
+    *WATCHER.lock().map_err(|e| e.to_string())? = Some(watcher);
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut events = vec![first];
+            loop {
+                match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                    Ok(Some(event)) => events.push(event),
+                    Ok(None) => return,
+                    Err(_) => break,
+                }
+            }
+
+            // 忽略规则需要读取 .syncignore，放到阻塞线程中处理
+            let (app, roots) = (app.clone(), roots.clone());
+            let _ = tokio::task::spawn_blocking(move || handle_events(&app, &roots, events)).await;
+        }
+    });
+
+    Ok(())
+}
+
+/// 停止文件监听
+#[tauri::command]
+pub fn stop_file_watcher() -> Result<(), String> {
+    *WATCHER.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
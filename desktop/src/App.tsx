@@ -5,6 +5,8 @@ import { useAuthStore } from '@/stores/auth';
 import { useSettingsStore } from '@/stores/settings';
 import { usePermissionsStore } from '@/stores/permissions';
 import { syncSettings, onEvent, EVENTS } from '@/lib/windowEvents';
+import { toast } from '@/hooks/use-toast';
+import type { ReconcileReport } from '@/lib/tauri';
 
 // ---- Error Boundary ----
 interface ErrorBoundaryProps {
@@ -150,6 +152,32 @@ function App() {
       if (unlisten) unlisten();
     };
   }, [settings.serverUrl, settings.rootDir]);
+
+  // 项目改名后本地文件夹已按项目编号整理
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+
+    const setupListener = async () => {
+      unlisten = await onEvent<ReconcileReport>('project-folder-reconciled', (report) => {
+        const name = report.path.split(/[\\/]/).pop();
+        const details = [
+          report.action === 'merged' ? `合并了 ${report.previous.length} 个旧文件夹` : '已重命名旧文件夹',
+          report.copies.length > 0 ? `${report.copies.length} 个同名文件保留为合并副本` : '',
+          report.errors.length > 0 ? `${report.errors.length} 个文件未能移动，旧文件夹已保留` : '',
+        ].filter(Boolean);
+        toast({
+          title: `项目文件夹已更新为 ${name}`,
+          description: details.join('，'),
+          variant: report.errors.length > 0 ? 'destructive' : 'default',
+        });
+      });
+    };
+
+    setupListener();
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
   
   return (
     <ErrorBoundary>
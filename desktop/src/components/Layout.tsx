@@ -4,7 +4,7 @@ import { LayoutDashboard, Kanban, ListTodo, FileCheck, FolderSync, DollarSign, S
 import { useAuthStore } from '@/stores/auth';
 import { cn, canViewFinance } from '@/lib/utils';
 import { onEvent, EVENTS } from '@/lib/windowEvents';
-import { useAutoSync } from '@/hooks/use-auto-sync';
+import { useSyncEngine } from '@/hooks/use-sync-engine';
 
 const isTauri = typeof window !== 'undefined' && '__TAURI__' in window;
 
@@ -35,8 +35,8 @@ export default function Layout() {
   const isManager = ['admin', 'super_admin', 'manager', 'tech_manager', 'design_manager'].includes(user?.role || '');
   const showFinance = canViewFinance(user?.role);
   
-  // 启动自动同步（后台扫描和上传）
-  useAutoSync();
+  // 登录信息和设置下发给后端同步引擎，自动同步由后端定时执行
+  useSyncEngine();
 
   // 切换主窗口置顶状态
   const toggleAlwaysOnTop = async () => {
@@ -0,0 +1,192 @@
+import { useState, useEffect } from 'react';
+import { AlertCircle, CheckCircle, FileDown } from 'lucide-react';
+import { useToast } from '@/hooks/use-toast';
+import { querySyncLog, exportSyncLogCsv, SyncLogEntry, SyncLogFilter, SyncLogAction } from '@/lib/tauri';
+
+const ACTION_LABELS: Record<SyncLogAction, string> = {
+  upload: '上传',
+  download: '下载',
+  delete_local: '删除本地',
+  delete_remote: '删除服务端',
+  conflict: '冲突',
+  rename: '重命名',
+  move: '移动',
+  resolve_conflict: '处理冲突',
+};
+
+const PAGE_SIZE = 100;
+
+interface Props {
+  /** 外部刷新计数，变化时重新加载 */
+  refreshKey: number;
+}
+
+/** 本地同步记录（Rust 端写入的活动日志） */
+export default function LocalSyncLog({ refreshKey }: Props) {
+  const { toast } = useToast();
+  const [entries, setEntries] = useState<SyncLogEntry[]>([]);
+  const [total, setTotal] = useState(0);
+  const [page, setPage] = useState(0);
+  const [loading, setLoading] = useState(false);
+  const [project, setProject] = useState('');
+  const [startDate, setStartDate] = useState('');
+  const [endDate, setEndDate] = useState('');
+  const [outcome, setOutcome] = useState<'' | 'success' | 'failed'>('');
+
+  const buildFilter = (): SyncLogFilter => ({
+    project: project.trim() || undefined,
+    start_date: startDate || undefined,
+    end_date: endDate || undefined,
+    outcome: outcome || undefined,
+  });
+
+  useEffect(() => {
+    loadEntries();
+  }, [refreshKey, project, startDate, endDate, outcome, page]);
+
+  const loadEntries = async () => {
+    setLoading(true);
+    try {
+      const result = await querySyncLog({ ...buildFilter(), limit: PAGE_SIZE, offset: page * PAGE_SIZE });
+      setEntries(result.items);
+      setTotal(result.total);
+    } catch (error) {
+      console.error('加载同步记录失败:', error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleExport = async () => {
+    try {
+      const count = await exportSyncLogCsv(buildFilter());
+      if (count !== null) {
+        toast({ title: '导出完成', description: `已导出 ${count} 条记录` });
+      }
+    } catch (error) {
+      toast({ title: '导出失败', description: String(error), variant: 'destructive' });
+    }
+  };
+
+  const formatSize = (bytes: number) => {
+    if (bytes === 0) return '-';
+    if (bytes < 1024) return bytes + ' B';
+    if (bytes < 1024 * 1024) return (bytes / 1024).toFixed(1) + ' KB';
+    return (bytes / (1024 * 1024)).toFixed(1) + ' MB';
+  };
+
+  const formatDuration = (ms: number) => (ms < 1000 ? `${ms} ms` : `${(ms / 1000).toFixed(1)} s`);
+
+  const pageCount = Math.max(1, Math.ceil(total / PAGE_SIZE));
+
+  return (
+    <div className="bg-white rounded-xl border overflow-hidden">
+      {/* 筛选 */}
+      <div className="px-4 py-3 border-b flex flex-wrap items-center gap-2">
+        <input
+          value={project}
+          onChange={(e) => { setProject(e.target.value); setPage(0); }}
+          placeholder="项目编号"
+          className="px-3 py-1.5 border rounded-lg text-sm w-32"
+        />
+        <input
+          type="date"
+          value={startDate}
+          onChange={(e) => { setStartDate(e.target.value); setPage(0); }}
+          className="px-3 py-1.5 border rounded-lg text-sm"
+        />
+        <span className="text-gray-400 text-sm">至</span>
+        <input
+          type="date"
+          value={endDate}
+          onChange={(e) => { setEndDate(e.target.value); setPage(0); }}
+          className="px-3 py-1.5 border rounded-lg text-sm"
+        />
+        <select
+          value={outcome}
+          onChange={(e) => { setOutcome(e.target.value as '' | 'success' | 'failed'); setPage(0); }}
+          className="px-3 py-1.5 border rounded-lg text-sm"
+        >
+          <option value="">全部结果</option>
+          <option value="success">成功</option>
+          <option value="failed">失败</option>
+        </select>
+        <div className="flex-1" />
+        <span className="text-sm text-gray-400">共 {total} 条</span>
+        <button
+          onClick={handleExport}
+          className="flex items-center gap-1 px-3 py-1.5 text-sm text-blue-600 hover:bg-blue-50 rounded-lg transition-colors"
+        >
+          <FileDown className="w-4 h-4" />
+          导出 CSV
+        </button>
+      </div>
+
+      {loading && entries.length === 0 ? (
+        <div className="flex items-center justify-center h-48 text-gray-400">加载中...</div>
+      ) : entries.length === 0 ? (
+        <div className="flex items-center justify-center h-48 text-gray-400">暂无同步记录</div>
+      ) : (
+        <table className="w-full">
+          <thead className="bg-gray-50">
+            <tr>
+              {['时间', '项目', '路径', '操作', '大小', '耗时', '结果'].map((title) => (
+                <th key={title} className="px-4 py-3 text-left text-xs font-semibold text-gray-500 uppercase">
+                  {title}
+                </th>
+              ))}
+            </tr>
+          </thead>
+          <tbody className="divide-y divide-gray-100">
+            {entries.map((entry, i) => (
+              <tr key={`${entry.timestamp}-${i}`} className="hover:bg-gray-50">
+                <td className="px-4 py-3 text-sm text-gray-500 whitespace-nowrap">{entry.timestamp}</td>
+                <td className="px-4 py-3 text-sm text-gray-600">{entry.project}</td>
+                <td className="px-4 py-3 text-sm text-gray-800" title={entry.detail}>
+                  <div className="truncate max-w-[280px]">{entry.path}</div>
+                  {entry.detail && <div className="text-xs text-gray-400 truncate max-w-[280px]">{entry.detail}</div>}
+                </td>
+                <td className="px-4 py-3 text-sm text-gray-600">{ACTION_LABELS[entry.action] || entry.action}</td>
+                <td className="px-4 py-3 text-sm text-gray-600">{formatSize(entry.bytes)}</td>
+                <td className="px-4 py-3 text-sm text-gray-600">{formatDuration(entry.duration_ms)}</td>
+                <td className="px-4 py-3">
+                  {entry.outcome === 'success' ? (
+                    <div className="flex items-center gap-1.5 text-sm text-green-600">
+                      <CheckCircle className="w-4 h-4 text-green-500" />
+                      成功
+                    </div>
+                  ) : (
+                    <div className="flex items-center gap-1.5 text-sm text-red-600" title={entry.error}>
+                      <AlertCircle className="w-4 h-4 text-red-500" />
+                      失败
+                    </div>
+                  )}
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+
+      {pageCount > 1 && (
+        <div className="px-4 py-2 border-t flex items-center justify-end gap-2 text-sm text-gray-500">
+          <button
+            disabled={page === 0}
+            onClick={() => setPage(page - 1)}
+            className="px-2 py-1 rounded hover:bg-gray-100 disabled:opacity-40"
+          >
+            上一页
+          </button>
+          <span>{page + 1} / {pageCount}</span>
+          <button
+            disabled={page + 1 >= pageCount}
+            onClick={() => setPage(page + 1)}
+            className="px-2 py-1 rounded hover:bg-gray-100 disabled:opacity-40"
+          >
+            下一页
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
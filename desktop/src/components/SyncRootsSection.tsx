@@ -0,0 +1,166 @@
+import { useState, useEffect } from 'react';
+import { AlertTriangle, FolderPlus, Trash2 } from 'lucide-react';
+import { toast } from '@/hooks/use-toast';
+import {
+  selectDirectory,
+  getSyncRoots,
+  saveSyncRoots,
+  findDuplicateProjects,
+  SyncRoot,
+  DuplicateGroup,
+} from '@/lib/tauri';
+
+/** 多个同步根目录（如 SSD 放进行中项目、NAS 放归档项目），各自设置启用、优先级和限速 */
+export default function SyncRootsSection() {
+  const [roots, setRoots] = useState<SyncRoot[]>([]);
+  const [duplicates, setDuplicates] = useState<DuplicateGroup[]>([]);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    load();
+  }, []);
+
+  const load = async () => {
+    try {
+      setRoots(await getSyncRoots());
+      setDuplicates(await findDuplicateProjects());
+    } catch (error) {
+      console.error('[SYNC_DEBUG] 加载根目录失败:', error);
+    }
+  };
+
+  const update = (index: number, patch: Partial<SyncRoot>) => {
+    setRoots(roots.map((r, i) => (i === index ? { ...r, ...patch } : r)));
+  };
+
+  const handleAdd = async () => {
+    const selected = await selectDirectory();
+    if (!selected || roots.some(r => r.path === selected)) return;
+    setRoots([
+      ...roots,
+      { path: selected, enabled: true, priority: 0, upload_limit_kbps: null, download_limit_kbps: null },
+    ]);
+  };
+
+  const handleSave = async () => {
+    setSaving(true);
+    try {
+      const found = await saveSyncRoots(roots);
+      setDuplicates(found);
+      toast({
+        title: '根目录已保存',
+        description: found.length > 0 ? `发现 ${found.length} 个项目在多个根目录中重复` : undefined,
+        variant: found.length > 0 ? 'destructive' : 'success',
+      });
+    } catch (error) {
+      toast({ title: '保存根目录失败', description: String(error), variant: 'destructive' });
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const parseLimit = (value: string) => {
+    const n = parseInt(value, 10);
+    return Number.isFinite(n) && n > 0 ? n : null;
+  };
+
+  return (
+    <section className="bg-surface-light border border-border-light rounded-lg p-5">
+      <div className="flex items-center justify-between mb-4">
+        <h2 className="font-medium text-text-main">多个根目录</h2>
+        <div className="flex gap-2">
+          <button
+            onClick={handleAdd}
+            className="flex items-center gap-1.5 px-3 py-1.5 border border-border-light rounded-lg text-sm hover:bg-background-light"
+          >
+            <FolderPlus className="w-4 h-4" />
+            添加
+          </button>
+          <button
+            onClick={handleSave}
+            disabled={saving}
+            className="px-3 py-1.5 bg-primary text-white text-sm rounded-lg hover:bg-primary/90 disabled:opacity-50"
+          >
+            保存
+          </button>
+        </div>
+      </div>
+
+      <div className="space-y-3">
+        {roots.map((root, i) => (
+          <div key={root.path} className="border border-border-light rounded-lg p-3 space-y-2">
+            <div className="flex items-center gap-2">
+              <input
+                type="checkbox"
+                checked={root.enabled}
+                onChange={(e) => update(i, { enabled: e.target.checked })}
+                title="参与自动同步"
+              />
+              <span className="flex-1 text-sm text-text-main truncate" title={root.path}>{root.path}</span>
+              <button
+                onClick={() => setRoots(roots.filter((_, j) => j !== i))}
+                className="p-1 text-text-secondary hover:text-red-500"
+                title="移除"
+              >
+                <Trash2 className="w-4 h-4" />
+              </button>
+            </div>
+            <div className="flex items-center gap-3 text-xs text-text-secondary">
+              <label className="flex items-center gap-1">
+                优先级
+                <input
+                  type="number"
+                  value={root.priority}
+                  onChange={(e) => update(i, { priority: parseInt(e.target.value, 10) || 0 })}
+                  className="w-16 px-2 py-1 border border-border-light rounded"
+                />
+              </label>
+              <label className="flex items-center gap-1">
+                上传限速
+                <input
+                  type="number"
+                  min={0}
+                  value={root.upload_limit_kbps ?? ''}
+                  onChange={(e) => update(i, { upload_limit_kbps: parseLimit(e.target.value) })}
+                  placeholder="不限"
+                  className="w-20 px-2 py-1 border border-border-light rounded"
+                />
+                KB/s
+              </label>
+              <label className="flex items-center gap-1">
+                下载限速
+                <input
+                  type="number"
+                  min={0}
+                  value={root.download_limit_kbps ?? ''}
+                  onChange={(e) => update(i, { download_limit_kbps: parseLimit(e.target.value) })}
+                  placeholder="不限"
+                  className="w-20 px-2 py-1 border border-border-light rounded"
+                />
+                KB/s
+              </label>
+            </div>
+          </div>
+        ))}
+      </div>
+
+      <p className="text-xs text-text-secondary mt-2">
+        优先级高的根目录先同步；同一项目出现在多个根目录时只同步优先级最高的位置
+      </p>
+
+      {duplicates.length > 0 && (
+        <div className="mt-3 p-3 bg-yellow-50 border border-yellow-200 rounded-lg text-xs text-yellow-800 space-y-1">
+          <div className="flex items-center gap-1 font-medium">
+            <AlertTriangle className="w-4 h-4" />
+            以下项目在多个根目录中重复
+          </div>
+          {duplicates.map(d => (
+            <div key={d.group_code}>
+              {d.group_code}：{d.paths.join('，')}
+            </div>
+          ))}
+        </div>
+      )}
+    </section>
+  );
+}
@@ -0,0 +1,38 @@
+import { useEffect } from 'react';
+import { useSettingsStore } from '@/stores/settings';
+import { useAuthStore } from '@/stores/auth';
+import { configureSyncEngine, resetSyncEngine, startSyncScheduler, stopSyncScheduler } from '@/lib/tauri';
+
+/** 后台定时同步的最短间隔（秒） */
+const MIN_SYNC_INTERVAL_SECS = 60;
+
+/**
+ * 将登录信息和设置下发给后端同步引擎
+ * 登录、修改服务器地址或根目录后重新配置，退出登录时清除；开启自动同步时由后端定时执行
+ */
+export function useSyncEngine() {
+  const { serverUrl, rootDir, autoSync, syncInterval } = useSettingsStore();
+  const { token, isAuthenticated } = useAuthStore();
+
+  useEffect(() => {
+    if (!isAuthenticated || !token || !serverUrl) {
+      stopSyncScheduler()
+        .then(() => resetSyncEngine())
+        .catch(() => {});
+      return;
+    }
+
+    const setup = async () => {
+      await configureSyncEngine({ server_url: serverUrl, token, root_dir: rootDir || '' });
+      console.log('[SyncEngine] 已配置同步引擎', { serverUrl, rootDir });
+
+      if (autoSync && rootDir) {
+        await startSyncScheduler({ interval_secs: Math.max(MIN_SYNC_INTERVAL_SECS, syncInterval) });
+        console.log(`[SyncEngine] 启动后台定时同步，间隔 ${Math.max(MIN_SYNC_INTERVAL_SECS, syncInterval)} 秒`);
+      } else {
+        await stopSyncScheduler();
+      }
+    };
+    setup().catch(err => console.error('[SyncEngine] 配置同步引擎失败:', err));
+  }, [isAuthenticated, token, serverUrl, rootDir, autoSync, syncInterval]);
+}
@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import { open } from '@tauri-apps/plugin-dialog';
+import { open, save } from '@tauri-apps/plugin-dialog';
 
 export interface GroupFolder {
   group_code: string;
@@ -8,14 +8,20 @@ export interface GroupFolder {
   has_works: boolean;
   has_models: boolean;
   has_customer: boolean;
+  folders: string[];
+  sync_mode: SyncMode;
 }
 
+export type SyncMode = 'full' | 'metadata_only' | 'on_demand' | 'excluded';
+
 export interface LocalFile {
   rel_path: string;
   filename: string;
   size: number;
   modified_at: number;
   is_dir: boolean;
+  /** 云端占位文件对应的服务端存储键，打开时才下载 */
+  placeholder?: string | null;
 }
 
 export interface FileMetadata {
@@ -48,9 +54,10 @@ export async function selectDirectory(): Promise<string | null> {
   }
 }
 
-export async function scanRootDirectory(rootPath: string): Promise<GroupFolder[]> {
+/** 不指定 rootPath 时扫描全部根目录 */
+export async function scanRootDirectory(rootPath?: string): Promise<GroupFolder[]> {
   try {
-    return await invoke<GroupFolder[]>('scan_root_directory', { rootPath });
+    return await invoke<GroupFolder[]>('scan_root_directory', { rootPath: rootPath || null });
   } catch (error) {
     console.error('[SYNC_DEBUG] 扫描目录失败:', error);
     throw error;
@@ -83,15 +90,52 @@ export async function getLocalFiles(
   }
 }
 
-export async function calculateFileHash(filePath: string): Promise<string> {
+/** sha256 为同步使用的默认算法，blake3 最快，md5 与 S3 ETag 一致 */
+export type HashAlgorithm = 'sha256' | 'blake3' | 'md5';
+
+/** hash-progress 事件：大文件哈希计算进度 */
+export interface HashProgress {
+  file_path: string;
+  algorithm: HashAlgorithm;
+  processed: number;
+  total: number;
+}
+
+export async function calculateFileHash(filePath: string, algorithm?: HashAlgorithm): Promise<string> {
   try {
-    return await invoke<string>('calculate_file_hash', { filePath });
+    return await invoke<string>('calculate_file_hash', { filePath, algorithm });
   } catch (error) {
     console.error('[SYNC_DEBUG] 计算文件哈希失败:', error);
     throw error;
   }
 }
 
+/** 批量命令中单个路径的结果 */
+export interface PathResult<T> {
+  value: T | null;
+  error: string | null;
+}
+
+/**
+ * 批量计算文件哈希，结果按路径返回；concurrency 未指定时使用 CPU 核数
+ */
+export async function calculateFileHashBatch(
+  paths: string[],
+  algorithm?: HashAlgorithm,
+  concurrency?: number
+): Promise<Record<string, PathResult<string>>> {
+  try {
+    return await invoke<Record<string, PathResult<string>>>('calculate_file_hash_batch', {
+      paths,
+      algorithm,
+      concurrency,
+    });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 批量计算文件哈希失败:', error);
+    throw error;
+  }
+}
+
 export async function getFileMetadata(filePath: string): Promise<FileMetadata> {
   try {
     return await invoke<FileMetadata>('get_file_metadata', { filePath });
@@ -101,13 +145,31 @@ export async function getFileMetadata(filePath: string): Promise<FileMetadata> {
   }
 }
 
+/**
+ * 批量获取文件元数据，结果按路径返回；concurrency 未指定时使用 CPU 核数
+ */
+export async function getFileMetadataBatch(
+  paths: string[],
+  concurrency?: number
+): Promise<Record<string, PathResult<FileMetadata>>> {
+  try {
+    return await invoke<Record<string, PathResult<FileMetadata>>>('get_file_metadata_batch', {
+      paths,
+      concurrency,
+    });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 批量获取文件元数据失败:', error);
+    throw error;
+  }
+}
+
 export async function readFileChunk(
   filePath: string,
   offset: number,
   length: number
 ): Promise<Uint8Array> {
   try {
-    const data = await invoke<number[]>('read_file_chunk', {
+    const data = await invoke<ArrayBuffer>('read_file_chunk', {
       filePath,
       offset,
       length,
@@ -128,17 +190,30 @@ export async function getMimeType(filePath: string): Promise<string> {
   }
 }
 
+/**
+ * 写入文件分片（二进制传输）
+ * @param append 未指定 offset 时，true 追加，false 新建文件
+ * @param offset 指定时在该位置覆盖写入，不截断文件，分片可乱序写入
+ */
 export async function writeFileChunk(
   filePath: string,
   data: Uint8Array,
-  append: boolean = false
+  append: boolean = false,
+  offset?: number
 ): Promise<number> {
   try {
-    return await invoke<number>('write_file_chunk', {
-      filePath,
-      data: Array.from(data),
-      append,
-    });
+    const headers: Record<string, string> = {
+      'x-file-path': encodeURIComponent(filePath),
+      'x-append': String(append && offset === undefined),
+    };
+    if (offset !== undefined) {
+      headers['x-offset'] = String(offset);
+    } else if (!append) {
+      // 新建文件：从头写入并清空原有内容
+      headers['x-offset'] = '0';
+      headers['x-truncate'] = 'true';
+    }
+    return await invoke<number>('write_file_chunk', data, { headers });
   } catch (error) {
     console.error('[SYNC_DEBUG] 写入文件失败:', error);
     throw error;
@@ -154,6 +229,679 @@ export async function ensureDirectory(dirPath: string): Promise<void> {
   }
 }
 
+export async function isIgnored(path: string): Promise<boolean> {
+  try {
+    return await invoke<boolean>('is_ignored', { path });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 检查忽略规则失败:', error);
+    throw error;
+  }
+}
+
+export interface WaitingFile {
+  path: string;
+  reason: string;
+}
+
+export interface SyncStatus {
+  is_syncing: boolean;
+  last_sync: string | null;
+  pending_uploads: number;
+  pending_downloads: number;
+  waiting: WaitingFile[];
+  paused_projects: string[];
+}
+
+export interface GuardConfig {
+  enabled: boolean;
+  window_secs: number;
+  project_max_deletions: number;
+  project_max_modifications: number;
+  global_max_deletions: number;
+  global_max_modifications: number;
+}
+
+export interface SyncEngineConfig {
+  server_url: string;
+  token?: string | null;
+  root_dir: string;
+  quiet_window_secs?: number;
+  guard?: Partial<GuardConfig>;
+  trash?: Partial<TrashPolicy>;
+  versions?: Partial<VersionPolicy>;
+  cache?: Partial<CachePolicy>;
+}
+
+export interface TrashPolicy {
+  max_age_days: number;
+  max_size_mb: number;
+}
+
+/** 作品文件本地版本保留策略 */
+export interface VersionPolicy {
+  enabled: boolean;
+  max_versions: number;
+  max_age_days: number;
+  max_size_mb: number;
+}
+
+/** 跨项目共享的下载缓存策略 */
+export interface CachePolicy {
+  enabled: boolean;
+  max_size_mb: number;
+}
+
+export interface PausedProject {
+  group_code: string;
+  deletions: number;
+  modifications: number;
+  samples: string[];
+  reason: string;
+  detected_at: number;
+}
+
+export interface SyncSummary {
+  uploaded: number;
+  downloaded: number;
+  deleted_local: number;
+  deleted_remote: number;
+  conflicts: number;
+  waiting: number;
+  paused: string[];
+  errors: string[];
+}
+
+export async function getSyncStatus(): Promise<SyncStatus> {
+  try {
+    return await invoke<SyncStatus>('get_sync_status');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取同步状态失败:', error);
+    throw error;
+  }
+}
+
+export async function configureSyncEngine(config: SyncEngineConfig): Promise<void> {
+  try {
+    await invoke<void>('configure_sync_engine', { config });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 配置同步引擎失败:', error);
+    throw error;
+  }
+}
+
+export async function resetSyncEngine(): Promise<void> {
+  try {
+    await invoke<void>('reset_sync_engine');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 清除同步引擎配置失败:', error);
+    throw error;
+  }
+}
+
+export async function runSync(): Promise<SyncSummary> {
+  try {
+    return await invoke<SyncSummary>('run_sync');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 执行同步失败:', error);
+    throw error;
+  }
+}
+
+export async function getPausedProjects(): Promise<PausedProject[]> {
+  try {
+    return await invoke<PausedProject[]>('get_paused_projects');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取暂停项目失败:', error);
+    throw error;
+  }
+}
+
+export async function confirmSyncChanges(groupCode: string): Promise<void> {
+  try {
+    await invoke<void>('confirm_sync_changes', { groupCode });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 确认批量变更失败:', error);
+    throw error;
+  }
+}
+
+export type TrashReason = 'remote_deleted' | 'remote_overwritten' | 'conflict_replaced' | 'merge_duplicate';
+
+export interface TrashEntry {
+  id: string;
+  original_path: string;
+  size: number;
+  deleted_at: number;
+  reason: TrashReason;
+}
+
+export async function listTrash(rootDir: string): Promise<TrashEntry[]> {
+  try {
+    return await invoke<TrashEntry[]>('list_trash', { rootDir });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取回收站失败:', error);
+    throw error;
+  }
+}
+
+export async function restoreFromTrash(rootDir: string, id: string): Promise<string> {
+  try {
+    return await invoke<string>('restore_from_trash', { rootDir, id });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 恢复文件失败:', error);
+    throw error;
+  }
+}
+
+export async function purgeTrash(rootDir: string, ids?: string[]): Promise<number> {
+  try {
+    return await invoke<number>('purge_trash', { rootDir, ids: ids ?? null });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 清空回收站失败:', error);
+    throw error;
+  }
+}
+
+export interface RemoteResource {
+  rel_path: string;
+  filename: string;
+  size: number;
+  modified_at: string | null;
+  storage_key: string;
+  is_dir: boolean;
+  hash?: string | null;
+  /** 对象存储 ETag，分片上传的对象为 md5(各分片 MD5)-分片数 */
+  etag?: string | null;
+  part_size?: number | null;
+}
+
+export interface ConflictRecord {
+  id: string;
+  key: string;
+  group_code: string;
+  asset_type: string;
+  folder: string;
+  rel_path: string;
+  local_path: string;
+  copy_path: string;
+  remote: RemoteResource;
+  detected_at: number;
+}
+
+export type ConflictResolution = 'keep_local' | 'keep_remote' | 'keep_both';
+
+export async function listConflicts(): Promise<ConflictRecord[]> {
+  try {
+    return await invoke<ConflictRecord[]>('list_conflicts');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取冲突列表失败:', error);
+    throw error;
+  }
+}
+
+export async function resolveConflict(id: string, resolution: ConflictResolution): Promise<void> {
+  try {
+    await invoke<void>('resolve_conflict', { id, resolution });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 处理冲突失败:', error);
+    throw error;
+  }
+}
+
+export interface ProjectSyncSetting {
+  mode: SyncMode;
+  excluded_folders: string[];
+}
+
+export interface SelectiveSyncSettings {
+  default_mode: SyncMode;
+  excluded_folders: string[];
+  projects: Record<string, ProjectSyncSetting>;
+}
+
+export interface EvictResult {
+  removed: number;
+  freed_bytes: number;
+  kept: string[];
+}
+
+export async function getSelectiveSync(): Promise<SelectiveSyncSettings> {
+  try {
+    return await invoke<SelectiveSyncSettings>('get_selective_sync');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取选择性同步设置失败:', error);
+    throw error;
+  }
+}
+
+export async function saveSelectiveSync(settings: SelectiveSyncSettings): Promise<void> {
+  try {
+    await invoke<void>('save_selective_sync', { settings });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 保存选择性同步设置失败:', error);
+    throw error;
+  }
+}
+
+export async function setProjectSyncMode(groupCode: string, mode: SyncMode): Promise<void> {
+  try {
+    await invoke<void>('set_project_sync_mode', { groupCode, mode });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 设置项目同步方式失败:', error);
+    throw error;
+  }
+}
+
+export async function getProjectListing(groupCode: string): Promise<Record<string, RemoteResource[]>> {
+  try {
+    return await invoke<Record<string, RemoteResource[]>>('get_project_listing', { groupCode });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取项目文件列表失败:', error);
+    throw error;
+  }
+}
+
+export async function evictProject(groupCode: string): Promise<EvictResult> {
+  try {
+    return await invoke<EvictResult>('evict_project', { groupCode });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 移除项目本地文件失败:', error);
+    throw error;
+  }
+}
+
+export interface TimeWindow {
+  start: string;
+  end: string;
+  days?: number[];
+}
+
+export interface SchedulerConfig {
+  interval_secs: number;
+  windows?: TimeWindow[];
+  max_backoff_secs?: number;
+}
+
+export interface SchedulerStatus {
+  running: boolean;
+  config: SchedulerConfig | null;
+  next_run_at: string | null;
+  consecutive_failures: number;
+  last_error: string | null;
+}
+
+export async function startSyncScheduler(config?: SchedulerConfig): Promise<void> {
+  try {
+    await invoke<void>('start_sync_scheduler', { config: config ?? null });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 启动定时同步失败:', error);
+    throw error;
+  }
+}
+
+export async function stopSyncScheduler(): Promise<void> {
+  try {
+    await invoke<void>('stop_sync_scheduler');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 停止定时同步失败:', error);
+    throw error;
+  }
+}
+
+export async function triggerSyncNow(): Promise<void> {
+  try {
+    await invoke<void>('trigger_sync_now');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 触发同步失败:', error);
+    throw error;
+  }
+}
+
+export async function getSyncSchedulerStatus(): Promise<SchedulerStatus> {
+  try {
+    return await invoke<SchedulerStatus>('get_sync_scheduler_status');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取定时同步状态失败:', error);
+    throw error;
+  }
+}
+
+export type SyncOperation =
+  | { type: 'upload'; group_code: string; asset_type: string; rel_path: string }
+  | { type: 'rename'; group_code: string; asset_type: string; old_rel_path: string; new_rel_path: string }
+  | { type: 'delete'; group_code: string; asset_type: string; rel_path: string }
+  | { type: 'rename_directory'; group_code: string; asset_type: string; old_dir: string; new_dir: string };
+
+export interface QueuedOperation {
+  id: number;
+  operation: SyncOperation;
+  created_at: number;
+  attempts: number;
+  last_error: string | null;
+}
+
+export interface QueueStatus {
+  online: boolean;
+  pending: QueuedOperation[];
+  failed: QueuedOperation[];
+}
+
+export async function enqueueSyncOperation(operation: SyncOperation): Promise<void> {
+  try {
+    await invoke<void>('enqueue_sync_operation', { operation });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 加入离线队列失败:', error);
+    throw error;
+  }
+}
+
+export async function getSyncQueue(): Promise<QueueStatus> {
+  try {
+    return await invoke<QueueStatus>('get_sync_queue');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取离线队列失败:', error);
+    throw error;
+  }
+}
+
+export async function retryFailedOperations(): Promise<number> {
+  try {
+    return await invoke<number>('retry_failed_operations');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 重试失败操作失败:', error);
+    throw error;
+  }
+}
+
+export async function startFileWatcher(): Promise<void> {
+  try {
+    await invoke<void>('start_file_watcher');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 启动文件监听失败:', error);
+    throw error;
+  }
+}
+
+export async function stopFileWatcher(): Promise<void> {
+  try {
+    await invoke<void>('stop_file_watcher');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 停止文件监听失败:', error);
+    throw error;
+  }
+}
+
+export type SyncLogAction =
+  | 'upload'
+  | 'download'
+  | 'delete_local'
+  | 'delete_remote'
+  | 'conflict'
+  | 'rename'
+  | 'move'
+  | 'resolve_conflict';
+
+export interface SyncLogEntry {
+  timestamp: string;
+  project: string;
+  asset_type: string;
+  path: string;
+  action: SyncLogAction;
+  bytes: number;
+  duration_ms: number;
+  outcome: 'success' | 'failed';
+  detail?: string;
+  error?: string;
+}
+
+export interface SyncLogFilter {
+  project?: string;
+  start_date?: string;
+  end_date?: string;
+  outcome?: 'success' | 'failed';
+  action?: SyncLogAction;
+  limit?: number;
+  offset?: number;
+}
+
+export interface SyncLogPage {
+  total: number;
+  items: SyncLogEntry[];
+}
+
+export async function querySyncLog(filter?: SyncLogFilter): Promise<SyncLogPage> {
+  try {
+    return await invoke<SyncLogPage>('query_sync_log', { filter });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 查询同步日志失败:', error);
+    throw error;
+  }
+}
+
+/** 选择保存位置并导出 CSV，取消时返回 null */
+export async function exportSyncLogCsv(filter?: SyncLogFilter): Promise<number | null> {
+  const savePath = await save({
+    defaultPath: `同步日志_${new Date().toISOString().slice(0, 10)}.csv`,
+    filters: [{ name: 'CSV', extensions: ['csv'] }],
+  });
+  if (!savePath) return null;
+  try {
+    return await invoke<number>('export_sync_log_csv', { filter, savePath });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 导出同步日志失败:', error);
+    throw error;
+  }
+}
+
+export interface SyncRoot {
+  path: string;
+  enabled: boolean;
+  priority: number;
+  upload_limit_kbps: number | null;
+  download_limit_kbps: number | null;
+}
+
+export interface DuplicateGroup {
+  group_code: string;
+  paths: string[];
+}
+
+export async function getSyncRoots(): Promise<SyncRoot[]> {
+  try {
+    return await invoke<SyncRoot[]>('get_sync_roots');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取根目录列表失败:', error);
+    throw error;
+  }
+}
+
+/** 保存根目录列表，返回跨根目录重复的项目 */
+export async function saveSyncRoots(roots: SyncRoot[]): Promise<DuplicateGroup[]> {
+  try {
+    return await invoke<DuplicateGroup[]>('save_sync_roots', { roots });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 保存根目录列表失败:', error);
+    throw error;
+  }
+}
+
+export async function findDuplicateProjects(): Promise<DuplicateGroup[]> {
+  try {
+    return await invoke<DuplicateGroup[]>('find_duplicate_projects');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 检查重复项目失败:', error);
+    throw error;
+  }
+}
+
+export async function locateProject(groupCode: string): Promise<GroupFolder | null> {
+  try {
+    return await invoke<GroupFolder | null>('locate_project', { groupCode });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 查找项目失败:', error);
+    throw error;
+  }
+}
+
+export interface ReconcileReport {
+  group_code: string;
+  action: 'unchanged' | 'not_found' | 'renamed' | 'merged';
+  path: string;
+  previous: string[];
+  moved_files: number;
+  duplicates: number;
+  copies: string[];
+  errors: string[];
+}
+
+/** 按 CRM 当前名称整理项目文件夹（旧名称文件夹重命名，重复文件夹合并） */
+export async function reconcileProjectFolders(workDir: string, projectNames: string[]): Promise<ReconcileReport[]> {
+  try {
+    return await invoke<ReconcileReport[]>('reconcile_project_folders', { workDir, projectNames });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 整理项目文件夹失败:', error);
+    throw error;
+  }
+}
+
+export type ArchiveFormat = 'zip' | 'tar_zst';
+
+export interface ArchiveResult {
+  archive_path: string;
+  file_count: number;
+  total_size: number;
+  archive_size: number;
+  removed_local: boolean;
+}
+
+export interface RestoreResult {
+  group_code: string;
+  path: string;
+  restored_files: number;
+  copies: string[];
+}
+
+/** 归档进度（archive-progress 事件） */
+export interface ArchiveProgress {
+  group_code: string;
+  stage: 'packing' | 'verifying' | 'restoring';
+  processed: number;
+  total: number;
+}
+
+/**
+ * 将项目打包为 zip 或 tar.zst 归档并校验
+ * removeLocal 为 true 时删除本地文件，只保留占位文件夹
+ */
+export async function archiveProject(
+  groupCode: string,
+  dest: string,
+  format: ArchiveFormat = 'zip',
+  removeLocal = false
+): Promise<ArchiveResult> {
+  try {
+    return await invoke<ArchiveResult>('archive_project', { groupCode, dest, format, removeLocal });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 归档项目失败:', error);
+    throw error;
+  }
+}
+
+/**
+ * 从归档恢复项目，未指定归档文件时读取占位文件夹中的归档标记
+ */
+export async function restoreArchivedProject(options: {
+  groupCode?: string;
+  archivePath?: string;
+  destRoot?: string;
+}): Promise<RestoreResult> {
+  try {
+    return await invoke<RestoreResult>('restore_archived_project', {
+      groupCode: options.groupCode ?? null,
+      archivePath: options.archivePath ?? null,
+      destRoot: options.destRoot ?? null,
+    });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 恢复归档项目失败:', error);
+    throw error;
+  }
+}
+
+export interface FileVersion {
+  id: string;
+  hash: string;
+  size: number;
+  modified_at: number | null;
+  created_at: number;
+}
+
+/**
+ * 列出文件的本地版本（最新的在前）
+ */
+export async function listFileVersions(filePath: string): Promise<FileVersion[]> {
+  try {
+    return await invoke<FileVersion[]>('list_file_versions', { filePath });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取文件版本失败:', error);
+    throw error;
+  }
+}
+
+/**
+ * 恢复文件的本地版本，asCopy 为 true 时恢复为副本，返回恢复后的路径
+ */
+export async function restoreFileVersion(filePath: string, versionId: string, asCopy = false): Promise<string> {
+  try {
+    return await invoke<string>('restore_file_version', { filePath, versionId, asCopy });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 恢复文件版本失败:', error);
+    throw error;
+  }
+}
+
+/**
+ * 下载云端占位文件的真实内容，返回真实文件路径
+ */
+export async function hydratePlaceholder(filePath: string): Promise<string> {
+  try {
+    return await invoke<string>('hydrate_placeholder', { filePath });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 下载占位文件失败:', error);
+    throw error;
+  }
+}
+
+export interface CacheStats {
+  path: string | null;
+  entries: number;
+  total_size: number;
+  max_size_mb: number;
+}
+
+/**
+ * 获取下载缓存统计
+ */
+export async function getDownloadCacheStats(): Promise<CacheStats> {
+  try {
+    return await invoke<CacheStats>('get_download_cache_stats');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取下载缓存统计失败:', error);
+    throw error;
+  }
+}
+
+/**
+ * 清空下载缓存，不影响项目中已下载的文件
+ */
+export async function clearDownloadCache(): Promise<void> {
+  try {
+    await invoke('clear_download_cache');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 清空下载缓存失败:', error);
+    throw error;
+  }
+}
+
 export interface DownloadResult {
   task_id: string;
   success: boolean;
@@ -222,6 +970,52 @@ export async function getTempDir(): Promise<string> {
   }
 }
 
+export type SyncRule = 'download_only' | 'bidirectional' | 'manual_upload';
+
+export interface SeedFile {
+  name: string;
+  content: string;
+}
+
+export interface FolderTemplate {
+  name: string;
+  asset_type?: string | null;
+  rule?: SyncRule | null;
+  subfolders: FolderTemplate[];
+  seed_files: SeedFile[];
+}
+
+export interface FolderTemplateConfig {
+  folders: FolderTemplate[];
+}
+
+export async function getFolderTemplates(): Promise<FolderTemplateConfig> {
+  try {
+    return await invoke<FolderTemplateConfig>('get_folder_templates');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 获取文件夹模板失败:', error);
+    throw error;
+  }
+}
+
+export async function saveFolderTemplates(config: FolderTemplateConfig): Promise<void> {
+  try {
+    await invoke<void>('save_folder_templates', { config });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 保存文件夹模板失败:', error);
+    throw error;
+  }
+}
+
+export async function reloadFolderTemplates(): Promise<FolderTemplateConfig> {
+  try {
+    return await invoke<FolderTemplateConfig>('reload_folder_templates');
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 重新加载文件夹模板失败:', error);
+    throw error;
+  }
+}
+
 export interface FolderFileInfo {
   name: string;
   relative_path: string;
@@ -229,15 +1023,102 @@ export interface FolderFileInfo {
   size: number;
 }
 
-export async function scanFolderRecursive(folderPath: string): Promise<FolderFileInfo[]> {
+/** 目录遍历策略，未指定时不跟随链接 */
+export interface TraversalPolicy {
+  follow_links: boolean;
+  max_depth: number;
+  /** 0 表示不限 */
+  max_entries: number;
+}
+
+export interface SkippedEntry {
+  path: string;
+  reason: 'link' | 'loop' | 'max_depth' | 'max_entries';
+}
+
+export async function scanFolderRecursive(
+  folderPath: string,
+  policy?: Partial<TraversalPolicy>
+): Promise<FolderFileInfo[]> {
   try {
-    return await invoke<FolderFileInfo[]>('scan_folder_recursive', { folderPath });
+    return await invoke<FolderFileInfo[]>('scan_folder_recursive', { folderPath, policy });
   } catch (error) {
     console.error('[SYNC_DEBUG] 扫描文件夹失败:', error);
     throw error;
   }
 }
 
+/**
+ * 计算文件按 partSize（字节）分片上传后的 S3 ETag，用于不下载即与服务端对象比对
+ */
+export async function computeS3Etag(path: string, partSize: number): Promise<string> {
+  try {
+    return await invoke<string>('compute_s3_etag', { path, partSize });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 计算 ETag 失败:', error);
+    throw error;
+  }
+}
+
+/** scan-batch 事件：一批扫描结果及当前进度 */
+export interface ScanBatch {
+  scan_id: string;
+  entries: FolderFileInfo[];
+  files: number;
+  dirs: number;
+  total_size: number;
+  errors: number;
+  skipped: number;
+}
+
+export interface ScanEntryError {
+  path: string;
+  message: string;
+}
+
+/** scan-finished 事件：扫描结束汇总 */
+export interface ScanSummary {
+  scan_id: string;
+  path: string;
+  files: number;
+  dirs: number;
+  total_size: number;
+  errors: ScanEntryError[];
+  error_count: number;
+  skipped: SkippedEntry[];
+  skipped_count: number;
+  cancelled: boolean;
+  elapsed_ms: number;
+}
+
+/**
+ * 流式扫描文件夹，结果通过 scan-batch 事件分批发送，结束时发送 scan-finished 并返回汇总
+ */
+export async function scanFolderStream(
+  scanId: string,
+  path: string,
+  policy?: Partial<TraversalPolicy>
+): Promise<ScanSummary> {
+  try {
+    return await invoke<ScanSummary>('scan_folder_stream', { scanId, path, policy });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 流式扫描文件夹失败:', error);
+    throw error;
+  }
+}
+
+/**
+ * 取消流式扫描
+ */
+export async function cancelFolderScan(scanId: string): Promise<boolean> {
+  try {
+    return await invoke<boolean>('cancel_folder_scan', { scanId });
+  } catch (error) {
+    console.error('[SYNC_DEBUG] 取消扫描失败:', error);
+    throw error;
+  }
+}
+
 /**
  * 预览文件：优先检查本地项目文件夹，没有则下载到项目文件夹后打开
  * @param downloadUrl 下载URL
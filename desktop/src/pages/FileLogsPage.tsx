@@ -3,6 +3,7 @@ import { FolderSync, Upload, Download, AlertCircle, CheckCircle, Clock, RefreshC
 import { useAuthStore } from '@/stores/auth';
 import { useSettingsStore } from '@/stores/settings';
 import { useSyncStore } from '@/stores/sync';
+import LocalSyncLog from '@/components/LocalSyncLog';
 
 interface FileLog {
   id: number;
@@ -23,6 +24,9 @@ export default function FileLogsPage() {
   const [logs, setLogs] = useState<FileLog[]>([]);
   const [loading, setLoading] = useState(true);
   const [filter, setFilter] = useState<'all' | 'upload' | 'download'>('all');
+  // 服务端日志 / 本机同步记录
+  const [source, setSource] = useState<'server' | 'local'>('server');
+  const [localRefresh, setLocalRefresh] = useState(0);
 
   // 活跃任务（上传中/下载中/等待中），根据筛选过滤
   const activeUploadTasks = uploadTasks.filter(t => ['pending', 'uploading', 'paused'].includes(t.status));
@@ -103,25 +107,43 @@ export default function FileLogsPage() {
             <h1 className="text-lg font-semibold text-gray-800">文件日志</h1>
           </div>
           <div className="flex items-center gap-3">
-            {/* 筛选 */}
+            {/* 来源 */}
             <div className="flex border rounded-lg overflow-hidden">
-              {(['all', 'upload', 'download'] as const).map((type) => (
+              {(['server', 'local'] as const).map((type) => (
                 <button
                   key={type}
-                  onClick={() => setFilter(type)}
+                  onClick={() => setSource(type)}
                   className={`px-4 py-1.5 text-sm ${
-                    filter === type
+                    source === type
                       ? 'bg-blue-500 text-white'
                       : 'bg-white text-gray-600 hover:bg-gray-50'
                   }`}
                 >
-                  {type === 'all' ? '全部' : type === 'upload' ? '上传' : '下载'}
+                  {type === 'server' ? '服务端日志' : '本机同步记录'}
                 </button>
               ))}
             </div>
+            {/* 筛选 */}
+            {source === 'server' && (
+              <div className="flex border rounded-lg overflow-hidden">
+                {(['all', 'upload', 'download'] as const).map((type) => (
+                  <button
+                    key={type}
+                    onClick={() => setFilter(type)}
+                    className={`px-4 py-1.5 text-sm ${
+                      filter === type
+                        ? 'bg-blue-500 text-white'
+                        : 'bg-white text-gray-600 hover:bg-gray-50'
+                    }`}
+                  >
+                    {type === 'all' ? '全部' : type === 'upload' ? '上传' : '下载'}
+                  </button>
+                ))}
+              </div>
+            )}
             {/* 刷新 */}
             <button
-              onClick={loadLogs}
+              onClick={() => (source === 'server' ? loadLogs() : setLocalRefresh((n) => n + 1))}
               className="p-2 text-gray-500 hover:text-blue-500 hover:bg-blue-50 rounded-lg transition-colors"
             >
               <RefreshCw className={`w-5 h-5 ${loading ? 'animate-spin' : ''}`} />
@@ -247,7 +269,9 @@ export default function FileLogsPage() {
         )}
 
         {/* 日志列表 */}
-        {loading ? (
+        {source === 'local' ? (
+          <LocalSyncLog refreshKey={localRefresh} />
+        ) : loading ? (
           <div className="flex items-center justify-center h-64 text-gray-400">
             加载中...
           </div>
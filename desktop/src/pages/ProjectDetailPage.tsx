@@ -1579,7 +1579,7 @@ export default function ProjectDetailPage() {
     return name.replace(/[\/\\:*?"<>|]/g, '_');
   };
   
-  // 获取完整的本地路径（与 use-auto-sync.ts 一致）
+  // 获取完整的本地路径
   const getLocalBasePath = () => {
     const groupCode = getGroupFolderName();
     const projectName = getProjectFolderName();
@@ -6,6 +6,7 @@ import { toast } from '@/hooks/use-toast';
 import { selectDirectory, scanRootDirectory } from '@/lib/tauri';
 import { syncSettings, onEvent, EVENTS } from '@/lib/windowEvents';
 import { http } from '@/lib/http';
+import SyncRootsSection from '@/components/SyncRootsSection';
 
 interface AccelerationNode {
   id: number;
@@ -185,6 +186,9 @@ export default function SettingsPage() {
             </p>
           </section>
 
+          {/* 多个根目录 */}
+          <SyncRootsSection />
+
           {/* 服务器 */}
           <section className="bg-surface-light border border-border-light rounded-lg p-5">
             <h2 className="font-medium text-text-main mb-4">服务器</h2>
@@ -267,12 +271,12 @@ export default function SettingsPage() {
                     type="number"
                     value={syncInterval}
                     onChange={(e) => setSyncInterval(Number(e.target.value))}
-                    min={5}
-                    max={300}
+                    min={60}
+                    max={3600}
                     className="mt-1 w-32 px-3 py-2 border border-border-light rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-primary/50"
                   />
                   <p className="text-xs text-text-secondary mt-1">
-                    建议 10-30 秒，默认 10 秒
+                    由后台定时同步，最短 60 秒
                   </p>
                 </div>
               )}
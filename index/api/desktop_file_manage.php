@@ -4,6 +4,7 @@ require_once __DIR__ . '/../core/api_init.php';
 require_once __DIR__ . '/../core/desktop_auth.php';
 require_once __DIR__ . '/../core/rbac.php';
 require_once __DIR__ . '/../core/storage/storage_provider.php';
+require_once __DIR__ . '/../services/S3Service.php';
 
 $user = desktop_auth_require();
 
@@ -75,7 +76,41 @@ function can_manage_file(array $user, array $fileRow): bool {
     return false;
 }
 
-if (!in_array($action, ['rename', 'delete', 'batch_delete', 'rename_by_key', 'delete_by_key'], true)) {
+/**
+ * 能否改动群资源：管理员/主管，或被分配到该群任一项目的技术人员
+ */
+function can_manage_group(array $user, string $groupCode): bool {
+    if (is_manager_role((string)($user['role'] ?? ''))) return true;
+
+    $assigned = Db::queryOne(
+        'SELECT 1 FROM customers c
+         JOIN projects p ON p.customer_id = c.id AND p.deleted_at IS NULL
+         JOIN project_tech_assignments pta ON pta.project_id = p.id
+         WHERE c.group_code = ? AND c.deleted_at IS NULL AND pta.tech_user_id = ?
+         LIMIT 1',
+        [$groupCode, (int)$user['id']]
+    );
+    return (bool)$assigned;
+}
+
+/**
+ * 群资源的存储键（不含前缀），不在该群目录下或含有路径穿越时返回 null
+ */
+function group_resource_key(string $groupCode, string $storageKey): ?string {
+    if ($groupCode === '' || $storageKey === '' || filter_var($storageKey, FILTER_VALIDATE_URL)) {
+        return null;
+    }
+    $key = normalize_storage_key_no_prefix($storageKey);
+    if (strpos($key, "groups/{$groupCode}/") !== 0) {
+        return null;
+    }
+    if (in_array('..', explode('/', str_replace('\\', '/', $key)), true)) {
+        return null;
+    }
+    return $key;
+}
+
+if (!in_array($action, ['rename', 'delete', 'batch_delete', 'rename_by_key', 'delete_by_key', 'move_group_resource', 'delete_group_resource'], true)) {
     json_error(400, '参数错误');
 }
 
@@ -273,6 +308,68 @@ try {
         exit;
     }
 
+    if ($action === 'move_group_resource') {
+        $groupCode = (string)($input['group_code'] ?? '');
+        $oldKey = group_resource_key($groupCode, (string)($input['storage_key'] ?? ''));
+        $destKey = group_resource_key($groupCode, (string)($input['new_storage_key'] ?? ''));
+        if ($oldKey === null || $destKey === null) {
+            json_error(400, '参数错误');
+        }
+        if (!can_manage_group($user, $groupCode)) {
+            json_error(403, '无权限移动');
+        }
+
+        if ($destKey !== $oldKey) {
+            // 目标位置已有文件时不覆盖，由客户端按冲突处理
+            if ((new S3Service())->exists($destKey)) {
+                json_error(409, '目标位置已存在同名文件');
+            }
+            $storage = storage_provider();
+            if (!$storage->copyObject($oldKey, $destKey)) {
+                json_error(500, '移动失败：存储复制操作失败');
+            }
+            // 删除原文件失败时撤销复制，避免服务端出现两份
+            if (!$storage->deleteObject($oldKey)) {
+                $storage->deleteObject($destKey);
+                json_error(500, '移动失败：删除原文件失败');
+            }
+        }
+
+        echo json_encode([
+            'success' => true,
+            'message' => '移动成功',
+            'data' => [
+                'new_path' => $destKey,
+            ],
+        ], JSON_UNESCAPED_UNICODE);
+        exit;
+    }
+
+    if ($action === 'delete_group_resource') {
+        $groupCode = (string)($input['group_code'] ?? '');
+        $key = group_resource_key($groupCode, (string)($input['storage_key'] ?? ''));
+        if ($key === null) {
+            json_error(400, '参数错误');
+        }
+        if (!can_manage_group($user, $groupCode)) {
+            json_error(403, '无权限删除');
+        }
+
+        $storage = storage_provider();
+        if (!$storage->deleteObject($key)) {
+            json_error(500, '删除失败');
+        }
+
+        echo json_encode([
+            'success' => true,
+            'message' => '删除成功',
+            'data' => [
+                'storage_key' => $key,
+            ],
+        ], JSON_UNESCAPED_UNICODE);
+        exit;
+    }
+
     if ($action === 'batch_delete') {
         $ids = $input['ids'] ?? [];
         if (!is_array($ids) || empty($ids)) {
@@ -334,6 +334,7 @@ class S3Service
                 $key = '';
                 $size = 0;
                 $modified = '';
+                $etag = '';
                 
                 if (preg_match('/<Key>(.+?)<\/Key>/', $content, $m)) {
                     $key = html_entity_decode($m[1]);
@@ -344,6 +345,10 @@ class S3Service
                 if (preg_match('/<LastModified>(.+?)<\/LastModified>/', $content, $m)) {
                     $modified = $m[1];
                 }
+                // 分片上传的对象为 md5(各分片 MD5)-分片数，客户端据此比对内容
+                if (preg_match('/<ETag>(.+?)<\/ETag>/', $content, $m)) {
+                    $etag = trim(html_entity_decode($m[1]), '"');
+                }
                 
                 $keyLen = strlen($key);
                 if ($key === '' || $key === $prefix || ($keyLen > 0 && substr($key, $keyLen - 1) === '/')) {
@@ -360,6 +365,7 @@ class S3Service
                     'modified_at' => $modified,
                     'storage_key' => $key,
                     'is_dir' => false,
+                    'etag' => $etag !== '' ? $etag : null,
                 ];
             }
         }